@@ -0,0 +1,111 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// generates the tests behind advent_of_code::examples!. for each day, every example*.txt is run
+// through the operations listed in its sidecar, e.g. example2.txt and example2.expected:
+//
+//   # comments and blank lines are ignored
+//   part1: 5
+//   beams:
+//     a multi-line answer,
+//     indented by two spaces
+//
+// answers are compared without trailing newlines
+
+struct Expected {
+    op: String,
+    answer: String,
+}
+
+fn parse_expected(path: &Path) -> Vec<Expected> {
+    let contents = fs::read_to_string(path).unwrap();
+    let mut expected: Vec<Expected> = Vec::new();
+    let mut in_block = false;
+    for (i, line) in contents.lines().enumerate() {
+        if in_block && let Some(block_line) = line.strip_prefix("  ") {
+            let answer = &mut expected.last_mut().unwrap().answer;
+            if !answer.is_empty() {
+                answer.push('\n');
+            }
+            answer.push_str(block_line);
+            continue;
+        }
+        in_block = false;
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((op, answer)) = line.split_once(':') else {
+            panic!("{} line {}: expected 'op: answer'", path.display(), i + 1);
+        };
+        if op.is_empty() || !op.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            panic!("{} line {}: invalid op '{}'", path.display(), i + 1, op);
+        }
+        in_block = answer.is_empty();
+        expected.push(Expected {
+            op: op.to_string(),
+            answer: answer.trim().to_string(),
+        });
+    }
+    expected
+}
+
+fn test_name(stem: &str, op: &str) -> String {
+    let stem = stem.replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+    format!("{}_{}", stem, op)
+}
+
+fn generate_tests(day: &Path) -> String {
+    let mut examples = fs::read_dir(day)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            let name = path.file_name().unwrap().to_string_lossy();
+            name.starts_with("example") && name.ends_with(".txt")
+        })
+        .collect::<Vec<_>>();
+    examples.sort();
+    let mut tests = String::new();
+    for example in examples {
+        let sidecar = example.with_extension("expected");
+        if !sidecar.exists() {
+            println!(
+                "cargo::warning={} has no {}",
+                example.display(),
+                sidecar.display()
+            );
+            continue;
+        }
+        let stem = example.file_stem().unwrap().to_string_lossy();
+        let file_name = example.file_name().unwrap().to_string_lossy();
+        for Expected { op, answer } in parse_expected(&sidecar) {
+            writeln!(
+                tests,
+                r#"#[test]
+fn {name}() {{
+    let input = include_str!({path:?});
+    let answer = ::advent_of_code::Answer::into_output({op}(input.lines()));
+    assert_eq!(answer.trim_end_matches('\n'), {answer:?}, "{file_name} {op}");
+}}
+"#,
+                name = test_name(&stem, &op),
+                path = fs::canonicalize(&example).unwrap(),
+            )
+            .unwrap();
+        }
+    }
+    tests
+}
+
+fn main() {
+    println!("cargo::rerun-if-changed=src/bin");
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("examples");
+    fs::create_dir_all(&out_dir).unwrap();
+    for day in fs::read_dir("src/bin").unwrap() {
+        let day = day.unwrap().path();
+        if day.is_dir() {
+            let name = day.file_name().unwrap().to_string_lossy().into_owned();
+            fs::write(out_dir.join(name + ".rs"), generate_tests(&day)).unwrap();
+        }
+    }
+}
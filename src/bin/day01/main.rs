@@ -1,13 +1,19 @@
 use advent_of_code::{Named, Runner, create_runner, named};
-use num::traits::Euclid;
+use itertools::Itertools;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::iter::repeat_n;
 use std::str::{FromStr, Lines};
 
-type DialPosition = i16;
+type DialPosition = i64;
 type DialDistance = DialPosition;
+// rotations are worked out in a wider type, so no distance from any position can overflow
+type WideDistance = i128;
 
 const DIAL_SIZE: DialPosition = 100;
+const DIAL_START: DialPosition = 50;
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 enum DialRotation {
     Left(DialDistance),
     Right(DialDistance),
@@ -29,46 +35,73 @@ impl FromStr for DialRotation {
     }
 }
 
+impl fmt::Display for DialRotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DialRotation::Left(distance) => write!(f, "L{}", distance),
+            DialRotation::Right(distance) => write!(f, "R{}", distance),
+        }
+    }
+}
+
+impl DialRotation {
+    // negative distances turn the dial left
+    fn signed_distance(&self) -> WideDistance {
+        match self {
+            DialRotation::Left(distance) => -WideDistance::from(*distance),
+            DialRotation::Right(distance) => WideDistance::from(*distance),
+        }
+    }
+
+    fn apply(&self, dial: Dial) -> DialRotationResult {
+        dial.rotate(self.signed_distance())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Dial {
+    size: DialPosition,
+    position: DialPosition,
+}
+
 #[derive(Debug, PartialEq)]
 struct DialRotationResult {
     position: DialPosition,
-    zero_count: usize,
+    zero_count: u64,
 }
 
-impl DialRotation {
-    fn apply(&self, initial_position: DialPosition) -> DialRotationResult {
-        match self {
-            DialRotation::Left(distance) => {
-                let (zero_count, distance) = distance.div_rem_euclid(&DIAL_SIZE);
-                let mut position = initial_position - distance;
-                let mut zero_count = zero_count as usize;
-                if position < 0 {
-                    position += DIAL_SIZE;
-                    // if we started at zero, we already counted this wrap around last time
-                    if initial_position != 0 {
-                        zero_count += 1;
-                    }
-                } else if position == 0 {
-                    zero_count += 1;
-                }
-                DialRotationResult {
-                    position,
-                    zero_count,
-                }
-            }
-            DialRotation::Right(distance) => {
-                let (zero_count, distance) = distance.div_rem_euclid(&DIAL_SIZE);
-                let mut position = initial_position + distance;
-                let mut zero_count = zero_count as usize;
-                if position >= DIAL_SIZE {
-                    position -= DIAL_SIZE;
-                    zero_count += 1;
-                }
-                DialRotationResult {
-                    position,
-                    zero_count,
-                }
-            }
+impl Dial {
+    fn new(size: DialPosition, position: DialPosition) -> Self {
+        assert!(size > 0, "Dial size must be positive, got {}", size);
+        Self {
+            size,
+            position: position.rem_euclid(size),
+        }
+    }
+
+    // number of clicks that land on zero, counting the final position but not the initial one
+    fn zero_crossings(&self, distance: WideDistance) -> u64 {
+        // count multiples of size in (position, position + distance] when turning right
+        // or [position + distance, position) when turning left
+        let (position, size) = (
+            WideDistance::from(self.position),
+            WideDistance::from(self.size),
+        );
+        let (low, high) = if distance >= 0 {
+            (position, position + distance)
+        } else {
+            (position + distance - 1, position - 1)
+        };
+        // at most |distance| + 1, which fits for any i64 distance
+        (high.div_euclid(size) - low.div_euclid(size)) as u64
+    }
+
+    fn rotate(&self, distance: WideDistance) -> DialRotationResult {
+        let position = WideDistance::from(self.position) + distance;
+        DialRotationResult {
+            // less than size, so it fits back into a position
+            position: position.rem_euclid(WideDistance::from(self.size)) as DialPosition,
+            zero_count: self.zero_crossings(distance),
         }
     }
 }
@@ -77,9 +110,9 @@ fn part1(input: Lines) -> String {
     input
         .into_iter()
         .map(|line| line.parse::<DialRotation>().unwrap())
-        .scan(50, |position, rotation| {
-            let result = rotation.apply(*position);
-            *position = result.position;
+        .scan(Dial::new(DIAL_SIZE, DIAL_START), |dial, rotation| {
+            let result = rotation.apply(*dial);
+            dial.position = result.position;
             Some(result.position)
         })
         .filter(|&position| position == 0)
@@ -91,38 +124,205 @@ fn part2(input: Lines) -> String {
     input
         .into_iter()
         .map(|line| line.parse::<DialRotation>().unwrap())
-        .scan(50, |position, rotation| {
-            let result = rotation.apply(*position);
-            *position = result.position;
+        .scan(Dial::new(DIAL_SIZE, DIAL_START), |dial, rotation| {
+            let result = rotation.apply(*dial);
+            dial.position = result.position;
             Some(result.zero_count)
         })
-        .sum::<usize>()
+        .sum::<u64>()
         .to_string()
 }
 
+struct DialTraceStep {
+    rotation: DialRotation,
+    start: DialPosition,
+    result: DialRotationResult,
+}
+
+fn trace_rotations(input: Lines, mut dial: Dial) -> Vec<DialTraceStep> {
+    input
+        .map(|line| line.parse::<DialRotation>().unwrap())
+        .map(|rotation| {
+            let start = dial.position;
+            let result = rotation.apply(dial);
+            dial.position = result.position;
+            DialTraceStep {
+                rotation,
+                start,
+                result,
+            }
+        })
+        .collect()
+}
+
+const PATH_WIDTH: DialPosition = 50;
+
+// one line per rotation, 'o' is the start, '<' or '>' is the end, '-' is passed over
+// and '=' means at least one full spin
+fn render_path_step(step: &DialTraceStep, size: DialPosition) -> String {
+    let width = PATH_WIDTH.min(size);
+    let column = |position: DialPosition| {
+        (WideDistance::from(position) * WideDistance::from(width) / WideDistance::from(size))
+            as usize
+    };
+    let distance = step.rotation.signed_distance();
+    let mut line = if distance.abs() >= WideDistance::from(size) {
+        repeat_n('=', width as usize).collect_vec()
+    } else {
+        let mut line = repeat_n('.', width as usize).collect_vec();
+        (0..distance.abs())
+            .map(|click| {
+                let position = WideDistance::from(step.start) + click * distance.signum();
+                // less than size, so it fits back into a position
+                position.rem_euclid(WideDistance::from(size)) as DialPosition
+            })
+            .for_each(|position| line[column(position)] = '-');
+        line
+    };
+    line[column(step.start)] = 'o';
+    line[column(step.result.position)] = if distance < 0 { '<' } else { '>' };
+    format!(
+        "{:>6} |{}| {} -> {} ({})",
+        step.rotation.to_string(),
+        line.into_iter().collect::<String>(),
+        step.start,
+        step.result.position,
+        step.result.zero_count
+    )
+}
+
+const HISTOGRAM_WIDTH: usize = 40;
+
+fn render_histogram(steps: &[DialTraceStep]) -> String {
+    let counts: BTreeMap<DialPosition, usize> = steps
+        .iter()
+        .map(|step| step.result.position)
+        .counts()
+        .into_iter()
+        .collect();
+    let max_count = counts.values().copied().max().unwrap_or(1);
+    counts
+        .into_iter()
+        .map(|(position, count)| {
+            let bar = (count * HISTOGRAM_WIDTH).div_ceil(max_count);
+            format!("{:>3} |{} {}", position, "#".repeat(bar), count)
+        })
+        .join("\n")
+}
+
+fn trace(input: Lines) -> String {
+    let steps = trace_rotations(input, Dial::new(DIAL_SIZE, DIAL_START));
+    let rows = steps.iter().map(|step| {
+        format!(
+            "{},{},{},{}",
+            step.rotation, step.start, step.result.position, step.result.zero_count
+        )
+    });
+    ["rotation,start,end,zero_count".to_string()]
+        .into_iter()
+        .chain(rows)
+        .join("\n")
+}
+
+fn path(input: Lines) -> String {
+    trace_rotations(input, Dial::new(DIAL_SIZE, DIAL_START))
+        .iter()
+        .map(|step| render_path_step(step, DIAL_SIZE))
+        .join("\n")
+}
+
+fn histogram(input: Lines) -> String {
+    render_histogram(&trace_rotations(input, Dial::new(DIAL_SIZE, DIAL_START)))
+}
+
 fn main() {
-    let input = include_str!("input.txt");
     let runner: &Runner = create_runner!();
+    let input = &runner.load(include_str!("input.txt"));
     runner.run(named!(part1), input);
     runner.run(named!(part2), input);
+    runner.run_if_requested(named!(trace), input);
+    runner.run_if_requested(named!(path), input);
+    runner.run_if_requested(named!(histogram), input);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use advent_of_code::verify;
+    use advent_of_code::{check_oracle, examples};
+    use rand::Rng;
+
+    fn dial(position: DialPosition) -> Dial {
+        Dial::new(DIAL_SIZE, position)
+    }
+
+    fn simulate_clicks(dial: Dial, distance: WideDistance) -> DialRotationResult {
+        let step = distance.signum() as DialPosition;
+        let mut position = dial.position;
+        let mut zero_count = 0;
+        for _ in 0..distance.abs() {
+            position = (position + step).rem_euclid(dial.size);
+            if position == 0 {
+                zero_count += 1;
+            }
+        }
+        DialRotationResult {
+            position,
+            zero_count,
+        }
+    }
+
+    examples!();
 
     #[test]
-    fn example() {
+    fn example_trace() {
         let input = include_str!("example.txt");
-        verify!(part1, input, "3");
-        verify!(part2, input, "6");
+        let trace = trace(input.lines());
+        let mut lines = trace.lines();
+        assert_eq!(lines.next(), Some("rotation,start,end,zero_count"));
+        assert_eq!(lines.next(), Some("L68,50,82,1"));
+        assert_eq!(lines.next(), Some("L30,82,52,0"));
+        assert_eq!(lines.next(), Some("R48,52,0,1"));
+        assert_eq!(lines.count(), 7);
+    }
+
+    #[test]
+    fn example_path() {
+        let input = include_str!("example.txt");
+        let steps = trace_rotations(input.lines(), Dial::new(10, 5));
+        assert_eq!(
+            render_path_step(&steps[0], 10),
+            "   L68 |=====o=<==| 5 -> 7 (7)"
+        );
+        assert_eq!(
+            render_path_step(&steps[1], 10),
+            "   L30 |=======<==| 7 -> 7 (3)"
+        );
+        assert_eq!(
+            render_path_step(&steps[3], 10),
+            "    L5 |<----o....| 5 -> 0 (1)"
+        );
+    }
+
+    #[test]
+    fn example_histogram() {
+        let input = include_str!("example.txt");
+        let steps = trace_rotations(input.lines(), Dial::new(DIAL_SIZE, DIAL_START));
+        let histogram = render_histogram(&steps);
+        let mut lines = histogram.lines();
+        assert_eq!(
+            lines.next(),
+            Some(format!("  0 |{} 3", "#".repeat(40))).as_deref()
+        );
+        assert_eq!(
+            lines.next(),
+            Some(format!(" 14 |{} 1", "#".repeat(14))).as_deref()
+        );
     }
 
     #[test]
     fn rotate_no_zero_left() {
         assert_eq!(
-            DialRotation::Left(30).apply(82),
+            DialRotation::Left(30).apply(dial(82)),
             DialRotationResult {
                 position: 52,
                 zero_count: 0
@@ -133,7 +333,7 @@ mod tests {
     #[test]
     fn rotate_no_zero_right() {
         assert_eq!(
-            DialRotation::Right(22).apply(50),
+            DialRotation::Right(22).apply(dial(50)),
             DialRotationResult {
                 position: 72,
                 zero_count: 0
@@ -144,7 +344,7 @@ mod tests {
     #[test]
     fn rotate_from_zero_left() {
         assert_eq!(
-            DialRotation::Left(5).apply(0),
+            DialRotation::Left(5).apply(dial(0)),
             DialRotationResult {
                 position: 95,
                 zero_count: 0
@@ -155,7 +355,7 @@ mod tests {
     #[test]
     fn rotate_from_zero_right() {
         assert_eq!(
-            DialRotation::Right(14).apply(0),
+            DialRotation::Right(14).apply(dial(0)),
             DialRotationResult {
                 position: 14,
                 zero_count: 0
@@ -166,7 +366,7 @@ mod tests {
     #[test]
     fn rotate_to_zero_left() {
         assert_eq!(
-            DialRotation::Left(55).apply(55),
+            DialRotation::Left(55).apply(dial(55)),
             DialRotationResult {
                 position: 0,
                 zero_count: 1
@@ -177,7 +377,7 @@ mod tests {
     #[test]
     fn rotate_to_zero_right() {
         assert_eq!(
-            DialRotation::Right(48).apply(52),
+            DialRotation::Right(48).apply(dial(52)),
             DialRotationResult {
                 position: 0,
                 zero_count: 1
@@ -188,7 +388,7 @@ mod tests {
     #[test]
     fn rotate_past_zero_left() {
         assert_eq!(
-            DialRotation::Left(68).apply(50),
+            DialRotation::Left(68).apply(dial(50)),
             DialRotationResult {
                 position: 82,
                 zero_count: 1
@@ -199,7 +399,7 @@ mod tests {
     #[test]
     fn rotate_past_zero_right() {
         assert_eq!(
-            DialRotation::Right(60).apply(95),
+            DialRotation::Right(60).apply(dial(95)),
             DialRotationResult {
                 position: 55,
                 zero_count: 1
@@ -210,7 +410,7 @@ mod tests {
     #[test]
     fn rotate_multiple_spins_left() {
         assert_eq!(
-            DialRotation::Left(1022).apply(51),
+            DialRotation::Left(1022).apply(dial(51)),
             DialRotationResult {
                 position: 29,
                 zero_count: 10
@@ -218,7 +418,7 @@ mod tests {
             "simple"
         );
         assert_eq!(
-            DialRotation::Left(1022).apply(0),
+            DialRotation::Left(1022).apply(dial(0)),
             DialRotationResult {
                 position: 78,
                 zero_count: 10
@@ -226,7 +426,7 @@ mod tests {
             "from zero"
         );
         assert_eq!(
-            DialRotation::Left(1022).apply(22),
+            DialRotation::Left(1022).apply(dial(22)),
             DialRotationResult {
                 position: 0,
                 zero_count: 11
@@ -238,7 +438,7 @@ mod tests {
     #[test]
     fn rotate_multiple_spins_right() {
         assert_eq!(
-            DialRotation::Right(1022).apply(51),
+            DialRotation::Right(1022).apply(dial(51)),
             DialRotationResult {
                 position: 73,
                 zero_count: 10
@@ -246,7 +446,7 @@ mod tests {
             "simple"
         );
         assert_eq!(
-            DialRotation::Right(1022).apply(0),
+            DialRotation::Right(1022).apply(dial(0)),
             DialRotationResult {
                 position: 22,
                 zero_count: 10
@@ -254,7 +454,7 @@ mod tests {
             "from zero"
         );
         assert_eq!(
-            DialRotation::Right(1022).apply(78),
+            DialRotation::Right(1022).apply(dial(78)),
             DialRotationResult {
                 position: 0,
                 zero_count: 11
@@ -262,4 +462,81 @@ mod tests {
             "to zero"
         );
     }
+
+    #[test]
+    fn rotate_large_distance() {
+        assert_eq!(
+            DialRotation::Right(1_000_000_000_000_022).apply(dial(78)),
+            DialRotationResult {
+                position: 0,
+                zero_count: 10_000_000_000_001
+            }
+        );
+        assert_eq!(
+            DialRotation::Left(1_000_000_000_000_022).apply(dial(0)),
+            DialRotationResult {
+                position: 78,
+                zero_count: 10_000_000_000_000
+            }
+        );
+    }
+
+    #[test]
+    fn rotate_other_dial_sizes() {
+        assert_eq!(
+            DialRotation::Left(7).apply(Dial::new(3, 1)),
+            DialRotationResult {
+                position: 0,
+                zero_count: 3
+            }
+        );
+        assert_eq!(
+            DialRotation::Right(5).apply(Dial::new(1, 0)),
+            DialRotationResult {
+                position: 0,
+                zero_count: 5
+            }
+        );
+    }
+
+    #[test]
+    fn rotate_extreme_distances() {
+        assert_eq!(
+            DialRotation::Right(DialDistance::MAX).apply(dial(50)),
+            DialRotationResult {
+                position: 57,
+                zero_count: 92233720368547758
+            }
+        );
+        assert_eq!(
+            DialRotation::Left(DialDistance::MAX).apply(dial(50)),
+            DialRotationResult {
+                position: 43,
+                zero_count: 92233720368547758
+            }
+        );
+        assert_eq!(
+            DialRotation::Left(DialDistance::MIN).apply(Dial::new(DialPosition::MAX, -1)),
+            DialRotationResult {
+                position: 0,
+                zero_count: 2
+            }
+        );
+    }
+
+    #[test]
+    fn zero_crossings_match_clicks() {
+        check_oracle!(
+            |rng| {
+                let size = rng.random_range(1..=20);
+                (
+                    size,
+                    rng.random_range(0..size),
+                    rng.random_range(-100..=100),
+                )
+            },
+            |&(size, position, distance)| Dial::new(size, position).rotate(distance),
+            |&(size, position, distance)| simulate_clicks(Dial::new(size, position), distance)
+        );
+    }
 }
@@ -1,56 +1,273 @@
 use advent_of_code::{Named, Runner, create_runner, named};
 use itertools::Itertools;
 use num::traits::Euclid;
-use std::iter::successors;
+use std::iter::{once, repeat_n, successors};
 use std::ops::RangeInclusive;
 use std::str::{FromStr, Lines};
 
-type IdRangeNumber = u64;
+type IdRangeNumber = u128;
 
-struct IdRange {
-    range: RangeInclusive<IdRangeNumber>,
+#[derive(Clone, Copy, Debug)]
+struct Radix(IdRangeNumber);
+
+const DECIMAL: Radix = Radix(10);
+
+impl Radix {
+    fn num_digits(&self, n: IdRangeNumber) -> u32 {
+        n.checked_ilog(self.0).unwrap_or(0) + 1
+    }
+
+    fn pow(&self, exp: u32) -> Option<IdRangeNumber> {
+        self.0.checked_pow(exp)
+    }
+
+    // most significant digit first
+    fn digits(&self, n: IdRangeNumber) -> Vec<IdRangeNumber> {
+        let mut digits = successors(Some(n), |n| Some(n / self.0).filter(|n| *n > 0))
+            .map(|n| n % self.0)
+            .collect_vec();
+        digits.reverse();
+        digits
+    }
+
+    fn join_digits(
+        &self,
+        digits: impl IntoIterator<Item = IdRangeNumber>,
+    ) -> Option<IdRangeNumber> {
+        digits.into_iter().try_fold(0 as IdRangeNumber, |n, d| {
+            n.checked_mul(self.0)?.checked_add(d)
+        })
+    }
 }
 
-impl IdRange {
-    fn num_digits(n: IdRangeNumber) -> u32 {
-        n.checked_ilog10().unwrap_or(0) + 1
+trait IdPattern {
+    // smallest matching id which is at least `start`, or None if it doesn't fit in IdRangeNumber
+    fn next_from(&self, start: IdRangeNumber, radix: Radix) -> Option<IdRangeNumber>;
+}
+
+// patterns where an id is determined by its first few digits (the seed) and its length
+trait SeededPattern {
+    fn seed_digits(&self, id_digits: u32) -> Option<u32>;
+
+    // must be increasing in seed and start with the digits of seed
+    fn expand(&self, seed: IdRangeNumber, id_digits: u32, radix: Radix) -> Option<IdRangeNumber>;
+
+    fn next_seed(&self, start: IdRangeNumber, radix: Radix) -> Option<(IdRangeNumber, u32)> {
+        let start_digits = radix.num_digits(start);
+        if let Some(seed_digits) = self.seed_digits(start_digits) {
+            let seed = start / radix.pow(start_digits - seed_digits)?;
+            if self.expand(seed, start_digits, radix)? >= start {
+                return Some((seed, start_digits));
+            } else if seed + 1 < radix.pow(seed_digits)? {
+                return Some((seed + 1, start_digits));
+            }
+        }
+        // nothing left with this many digits, use the smallest seed of the next usable length
+        let (id_digits, seed_digits) = (start_digits + 1..=IdRangeNumber::BITS)
+            .find_map(|id_digits| Some((id_digits, self.seed_digits(id_digits)?)))?;
+        Some((radix.pow(seed_digits - 1)?, id_digits))
     }
+}
 
-    fn next_invalid_part(start: IdRangeNumber, num_parts: usize) -> IdRangeNumber {
-        let digits = Self::num_digits(start);
-        let (part_digits, remainder) = digits.div_rem_euclid(&(num_parts as u32));
-        if remainder != 0 {
-            (10 as IdRangeNumber).pow(part_digits)
+impl<P: SeededPattern> IdPattern for P {
+    fn next_from(&self, start: IdRangeNumber, radix: Radix) -> Option<IdRangeNumber> {
+        let (seed, id_digits) = self.next_seed(start, radix)?;
+        self.expand(seed, id_digits, radix)
+    }
+}
+
+// one block of digits repeated `num_parts` times
+struct Repeated {
+    num_parts: u32,
+}
+
+impl Repeated {
+    // every id with this many digits is its block multiplied by 1, 101, 10101, ...
+    // so the ids in a range are an arithmetic series
+    fn sum_with_digits(
+        &self,
+        range: &RangeInclusive<IdRangeNumber>,
+        id_digits: u32,
+        radix: Radix,
+    ) -> Option<IdRangeNumber> {
+        if self.seed_digits(id_digits).is_none() {
+            return Some(0);
+        }
+        let factor = self.expand(1, id_digits, radix)?;
+        let smallest = if id_digits == 1 {
+            0
+        } else {
+            radix.pow(id_digits - 1)?
+        };
+        let largest = radix.pow(id_digits).map_or(IdRangeNumber::MAX, |n| n - 1);
+        let first = (*range.start()).max(smallest).div_ceil(factor);
+        let last = (*range.end()).min(largest) / factor;
+        if first > last {
+            Some(0)
         } else {
-            let candidate = start / (10 as IdRangeNumber).pow(part_digits * (num_parts - 1) as u32);
-            if Self::invalid_from_part(candidate, num_parts) < start {
-                candidate + 1
-            } else {
-                candidate
+            arithmetic_series(first, last)?.checked_mul(factor)
+        }
+    }
+}
+
+impl SeededPattern for Repeated {
+    fn seed_digits(&self, id_digits: u32) -> Option<u32> {
+        let (part_digits, remainder) = id_digits.div_rem_euclid(&self.num_parts);
+        (remainder == 0).then_some(part_digits)
+    }
+
+    fn expand(&self, seed: IdRangeNumber, id_digits: u32, radix: Radix) -> Option<IdRangeNumber> {
+        let factor = radix.pow(id_digits / self.num_parts)?;
+        (0..self.num_parts).try_fold(0 as IdRangeNumber, |id, _| {
+            id.checked_mul(factor)?.checked_add(seed)
+        })
+    }
+}
+
+struct Palindrome;
+
+impl SeededPattern for Palindrome {
+    fn seed_digits(&self, id_digits: u32) -> Option<u32> {
+        Some(id_digits.div_ceil(2))
+    }
+
+    fn expand(&self, seed: IdRangeNumber, id_digits: u32, radix: Radix) -> Option<IdRangeNumber> {
+        // the middle digit of an odd length palindrome isn't mirrored
+        let mirrored = if id_digits % 2 == 1 {
+            seed / radix.0
+        } else {
+            seed
+        };
+        radix
+            .digits(mirrored)
+            .into_iter()
+            .rev()
+            .take((id_digits / 2) as usize)
+            .try_fold(seed, |id, d| id.checked_mul(radix.0)?.checked_add(d))
+    }
+}
+
+// ids made only of the allowed digits, which must all be digits of the radix it's used with
+#[derive(Clone)]
+struct DigitSet {
+    allowed: Vec<IdRangeNumber>,
+}
+
+impl DigitSet {
+    fn new(allowed: impl IntoIterator<Item = IdRangeNumber>, radix: Radix) -> Result<Self, String> {
+        let allowed = allowed.into_iter().sorted().dedup().collect_vec();
+        match allowed.iter().find(|&&d| d >= radix.0) {
+            Some(d) => Err(format!("{} is not a digit in base {}", d, radix.0)),
+            None => Ok(Self { allowed }),
+        }
+    }
+}
+
+impl IdPattern for DigitSet {
+    fn next_from(&self, start: IdRangeNumber, radix: Radix) -> Option<IdRangeNumber> {
+        let digits = radix.digits(start);
+        let smallest = *self.allowed.first()?;
+        let Some(invalid) = digits
+            .iter()
+            .position(|d| self.allowed.binary_search(d).is_err())
+        else {
+            return Some(start);
+        };
+        // raise the first invalid digit, or an earlier one if it can't be raised,
+        // then fill the rest with the smallest allowed digit
+        let raised = (0..=invalid).rev().find_map(|i| {
+            let raised = self.allowed.iter().find(|&&d| d > digits[i])?;
+            Some((i, *raised))
+        });
+        match raised {
+            Some((i, raised)) => radix.join_digits(
+                digits[..i]
+                    .iter()
+                    .copied()
+                    .chain(once(raised))
+                    .chain(repeat_n(smallest, digits.len() - i - 1)),
+            ),
+            None => {
+                let leading = *self.allowed.iter().find(|&&d| d > 0)?;
+                radix.join_digits(once(leading).chain(repeat_n(smallest, digits.len())))
             }
         }
     }
+}
 
-    fn invalid_from_part(part: IdRangeNumber, num_parts: usize) -> IdRangeNumber {
-        let digits = Self::num_digits(part);
-        let factor = (10 as IdRangeNumber).pow(digits);
-        successors(Some(1), |n| Some(n * factor))
-            .take(num_parts)
-            .map(|n| n * part)
-            .sum()
+struct IdRange {
+    range: RangeInclusive<IdRangeNumber>,
+}
+
+impl IdRange {
+    fn matching_ids<P: IdPattern>(
+        &self,
+        pattern: P,
+        radix: Radix,
+    ) -> impl Iterator<Item = IdRangeNumber> {
+        successors(pattern.next_from(*self.range.start(), radix), move |&id| {
+            pattern.next_from(id.checked_add(1)?, radix)
+        })
+        .take_while(|id| id <= self.range.end())
     }
 
-    fn invalid_ids(&self, num_parts: usize) -> impl Iterator<Item = IdRangeNumber> {
-        (Self::next_invalid_part(*self.range.start(), num_parts)..)
-            .map(move |part| Self::invalid_from_part(part, num_parts))
-            .take_while(|id| id <= self.range.end())
+    fn id_digits(&self, radix: Radix) -> RangeInclusive<u32> {
+        radix.num_digits(*self.range.start())..=radix.num_digits(*self.range.end())
     }
 
-    fn invalid_ids_any(&self) -> impl Iterator<Item = IdRangeNumber> {
-        let digits = Self::num_digits(*self.range.end());
-        (2..=digits as usize)
-            .flat_map(|num_parts| self.invalid_ids(num_parts))
-            .unique()
+    // None if the sum doesn't fit in IdRangeNumber
+    fn invalid_ids_sum(&self, num_parts: u32, radix: Radix) -> Option<IdRangeNumber> {
+        let pattern = Repeated { num_parts };
+        self.id_digits(radix)
+            .try_fold(0 as IdRangeNumber, |sum, id_digits| {
+                sum.checked_add(pattern.sum_with_digits(&self.range, id_digits, radix)?)
+            })
+    }
+
+    // an id repeated with block size k is also repeated with every multiple of k dividing its
+    // length, so the overlaps are removed using inclusion-exclusion over the divisors
+    fn invalid_ids_any_sum(&self, radix: Radix) -> Option<IdRangeNumber> {
+        let mut added: IdRangeNumber = 0;
+        let mut removed: IdRangeNumber = 0;
+        for id_digits in self.id_digits(radix) {
+            for num_parts in (2..=id_digits).filter(|n| id_digits.is_multiple_of(*n)) {
+                let sum = Repeated { num_parts }.sum_with_digits(&self.range, id_digits, radix)?;
+                match mobius(num_parts) {
+                    -1 => added = added.checked_add(sum)?,
+                    1 => removed = removed.checked_add(sum)?,
+                    _ => {}
+                }
+            }
+        }
+        added.checked_sub(removed)
+    }
+}
+
+fn mobius(n: u32) -> i32 {
+    let mut n = n;
+    let mut result = 1;
+    let mut factor = 2;
+    while factor * factor <= n {
+        if n.is_multiple_of(factor) {
+            n /= factor;
+            if n.is_multiple_of(factor) {
+                return 0;
+            }
+            result = -result;
+        }
+        factor += 1;
+    }
+    if n > 1 { -result } else { result }
+}
+
+fn arithmetic_series(first: IdRangeNumber, last: IdRangeNumber) -> Option<IdRangeNumber> {
+    let count = last - first + 1;
+    let ends = first.checked_add(last)?;
+    // either the count or the sum of the ends is even
+    if count.is_multiple_of(2) {
+        (count / 2).checked_mul(ends)
+    } else {
+        count.checked_mul(ends / 2)
     }
 }
 
@@ -79,7 +296,7 @@ fn parse_id_ranges(line: &str) -> Vec<IdRange> {
 fn part1(mut input: Lines) -> String {
     parse_id_ranges(input.next().unwrap())
         .into_iter()
-        .map(|id_range| id_range.invalid_ids(2).sum::<IdRangeNumber>())
+        .map(|id_range| id_range.invalid_ids_sum(2, DECIMAL).expect("sum fits"))
         .sum::<IdRangeNumber>()
         .to_string()
 }
@@ -87,75 +304,286 @@ fn part1(mut input: Lines) -> String {
 fn part2(mut input: Lines) -> String {
     parse_id_ranges(input.next().unwrap())
         .into_iter()
-        .map(|id_range| id_range.invalid_ids_any().sum::<IdRangeNumber>())
+        .map(|id_range| id_range.invalid_ids_any_sum(DECIMAL).expect("sum fits"))
         .sum::<IdRangeNumber>()
         .to_string()
 }
 
+// how many ids in each range are palindromes, or are written with only 0s and 1s
+fn patterns(mut input: Lines) -> Result<String, String> {
+    let binary = DigitSet::new([0, 1], DECIMAL)?;
+    Ok(parse_id_ranges(input.next().unwrap())
+        .iter()
+        .map(|id_range| {
+            format!(
+                "{}-{}: {} palindromes, {} made of 0 and 1",
+                id_range.range.start(),
+                id_range.range.end(),
+                id_range.matching_ids(Palindrome, DECIMAL).count(),
+                id_range.matching_ids(binary.clone(), DECIMAL).count()
+            )
+        })
+        .join("\n"))
+}
+
 fn main() {
-    let input = include_str!("input.txt");
     let runner: &Runner = create_runner!();
+    let input = &runner.load(include_str!("input.txt"));
     runner.run(named!(part1), input);
     runner.run(named!(part2), input);
+    runner.run_if_requested(named!(patterns), input);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use advent_of_code::verify;
+    use advent_of_code::{check_oracle, examples};
+    use rand::Rng;
+    use rstest::rstest;
+
+    fn is_repeated(id: IdRangeNumber, num_parts: u32) -> bool {
+        let digits = id.to_string();
+        let num_parts = num_parts as usize;
+        digits.len().is_multiple_of(num_parts)
+            && digits == digits[..digits.len() / num_parts].repeat(num_parts)
+    }
+
+    fn random_range(rng: &mut impl Rng) -> (IdRangeNumber, IdRangeNumber) {
+        let start = rng.random_range(0..100_000);
+        (start, start + rng.random_range(0..1000))
+    }
+
+    examples!();
+
+    #[test]
+    fn invalid_ids_sum_match_brute_force() {
+        check_oracle!(
+            |rng| (random_range(rng), rng.random_range(2..=5)),
+            |&((start, end), num_parts)| {
+                IdRange { range: start..=end }.invalid_ids_sum(num_parts, DECIMAL)
+            },
+            |&((start, end), num_parts)| {
+                Some((start..=end).filter(|&id| is_repeated(id, num_parts)).sum())
+            }
+        );
+    }
 
     #[test]
-    fn example() {
-        let input = include_str!("example.txt");
-        verify!(part1, input, "1227775554");
-        verify!(part2, input, "4174379265");
+    fn invalid_ids_any_sum_match_brute_force() {
+        check_oracle!(
+            random_range,
+            |&(start, end)| IdRange { range: start..=end }.invalid_ids_any_sum(DECIMAL),
+            |&(start, end)| {
+                Some(
+                    (start..=end)
+                        .filter(|&id| (2..=10).any(|num_parts| is_repeated(id, num_parts)))
+                        .sum(),
+                )
+            }
+        );
+    }
+
+    #[test]
+    fn test_mobius() {
+        assert_eq!(
+            (1..=12).map(mobius).collect_vec(),
+            [1, -1, -1, 0, -1, 1, -1, 0, 0, 1, -1, 0]
+        );
+    }
+
+    #[test]
+    fn test_sum_up_to_u64_max() {
+        let max = u64::MAX as IdRangeNumber;
+        let split = 123_456_789_012_345;
+        let sum = |start, end| IdRange { range: start..=end }.invalid_ids_any_sum(DECIMAL);
+        let whole = sum(1, max).unwrap();
+        assert_eq!(
+            Some(whole),
+            sum(1, split).zip(sum(split + 1, max)).map(|(a, b)| a + b)
+        );
+        // 1844674407_1844674407 is the largest 2 part id which fits in u64
+        assert_eq!(
+            IdRange {
+                range: 18_446_744_071_844_674_407..=max
+            }
+            .invalid_ids_sum(2, DECIMAL),
+            Some(18_446_744_071_844_674_407)
+        );
+    }
+
+    #[rstest]
+    #[case(0, 11)]
+    #[case(11, 11)]
+    #[case(12, 22)]
+    #[case(23, 33)]
+    #[case(95, 99)]
+    #[case(998, 1010)]
+    #[case(1188511880, 1188511885)]
+    #[case(446443, 446446)]
+    #[case(38593856, 38593859)]
+    fn test_repeated_twice_next_from(
+        #[case] start: IdRangeNumber,
+        #[case] expected: IdRangeNumber,
+    ) {
+        assert_eq!(
+            Repeated { num_parts: 2 }.next_from(start, DECIMAL),
+            Some(expected)
+        );
+    }
+
+    #[rstest]
+    #[case(0, 111)]
+    #[case(99, 111)]
+    #[case(100, 111)]
+    #[case(500, 555)]
+    #[case(998, 999)]
+    #[case(118851188511880, 118851188511885)]
+    #[case(446443446, 446446446)]
+    fn test_repeated_three_times_next_from(
+        #[case] start: IdRangeNumber,
+        #[case] expected: IdRangeNumber,
+    ) {
+        assert_eq!(
+            Repeated { num_parts: 3 }.next_from(start, DECIMAL),
+            Some(expected)
+        );
     }
 
     #[test]
-    fn test_next_invalid_part_2() {
-        assert_eq!(IdRange::next_invalid_part(0, 2), 1);
-        assert_eq!(IdRange::next_invalid_part(11, 2), 1);
-        assert_eq!(IdRange::next_invalid_part(12, 2), 2);
-        assert_eq!(IdRange::next_invalid_part(22, 2), 2);
-        assert_eq!(IdRange::next_invalid_part(23, 2), 3);
-        assert_eq!(IdRange::next_invalid_part(95, 2), 9);
-        assert_eq!(IdRange::next_invalid_part(998, 2), 10);
-        assert_eq!(IdRange::next_invalid_part(1188511880, 2), 11885);
-        assert_eq!(IdRange::next_invalid_part(446443, 2), 446);
-        assert_eq!(IdRange::next_invalid_part(38593856, 2), 3859);
+    fn test_repeated_expand() {
+        let twice = Repeated { num_parts: 2 };
+        assert_eq!(twice.expand(0, 2, DECIMAL), Some(0));
+        assert_eq!(twice.expand(9, 2, DECIMAL), Some(99));
+        assert_eq!(twice.expand(12, 4, DECIMAL), Some(1212));
+        assert_eq!(twice.expand(3859, 8, DECIMAL), Some(38593859));
+        let three_times = Repeated { num_parts: 3 };
+        assert_eq!(three_times.expand(1, 3, DECIMAL), Some(111));
+        assert_eq!(three_times.expand(10, 6, DECIMAL), Some(101010));
+        assert_eq!(three_times.expand(3859, 12, DECIMAL), Some(385938593859));
+        assert_eq!(twice.expand(1, 2, Radix(2)), Some(0b11));
     }
 
     #[test]
-    fn test_next_invalid_part_3() {
-        assert_eq!(IdRange::next_invalid_part(0, 3), 1);
-        assert_eq!(IdRange::next_invalid_part(11, 3), 1);
-        assert_eq!(IdRange::next_invalid_part(99, 3), 1);
-        assert_eq!(IdRange::next_invalid_part(100, 3), 1);
-        assert_eq!(IdRange::next_invalid_part(500, 3), 5);
-        assert_eq!(IdRange::next_invalid_part(998, 3), 9);
-        assert_eq!(IdRange::next_invalid_part(118851188511880, 3), 11885);
-        assert_eq!(IdRange::next_invalid_part(446443446, 3), 446);
+    fn test_palindromes() {
+        let range = IdRange { range: 95..=212 };
+        assert_eq!(
+            range.matching_ids(Palindrome, DECIMAL).collect_vec(),
+            [
+                99, 101, 111, 121, 131, 141, 151, 161, 171, 181, 191, 202, 212
+            ]
+        );
+        let range = IdRange { range: 0..=9 };
+        assert_eq!(
+            range.matching_ids(Palindrome, Radix(2)).collect_vec(),
+            [0b0, 0b1, 0b11, 0b101, 0b111, 0b1001]
+        );
+        assert_eq!(Palindrome.next_from(12321, DECIMAL), Some(12321));
+        assert_eq!(Palindrome.next_from(12322, DECIMAL), Some(12421));
     }
 
     #[test]
-    fn test_invalid_from_part_2() {
-        assert_eq!(IdRange::invalid_from_part(0, 2), 0);
-        assert_eq!(IdRange::invalid_from_part(1, 2), 11);
-        assert_eq!(IdRange::invalid_from_part(9, 2), 99);
-        assert_eq!(IdRange::invalid_from_part(10, 2), 1010);
-        assert_eq!(IdRange::invalid_from_part(12, 2), 1212);
-        assert_eq!(IdRange::invalid_from_part(99, 2), 9999);
-        assert_eq!(IdRange::invalid_from_part(3859, 2), 38593859);
+    fn test_digit_set() {
+        let pattern = DigitSet::new([0, 7], Radix(16)).unwrap();
+        assert_eq!(pattern.next_from(0, DECIMAL), Some(0));
+        assert_eq!(pattern.next_from(1, DECIMAL), Some(7));
+        assert_eq!(pattern.next_from(8, DECIMAL), Some(70));
+        assert_eq!(pattern.next_from(708, DECIMAL), Some(770));
+        assert_eq!(pattern.next_from(778, DECIMAL), Some(7000));
+        assert_eq!(pattern.next_from(0xf1, Radix(16)), Some(0x700));
+        assert_eq!(pattern.next_from(70077, DECIMAL), Some(70077));
+        assert_eq!(pattern.next_from(70177, DECIMAL), Some(70700));
+        assert_eq!(
+            DigitSet::new([0, 7, 10], DECIMAL).err(),
+            Some("10 is not a digit in base 10".to_string())
+        );
     }
 
     #[test]
-    fn test_invalid_from_part_3() {
-        assert_eq!(IdRange::invalid_from_part(0, 3), 0);
-        assert_eq!(IdRange::invalid_from_part(1, 3), 111);
-        assert_eq!(IdRange::invalid_from_part(9, 3), 999);
-        assert_eq!(IdRange::invalid_from_part(10, 3), 101010);
-        assert_eq!(IdRange::invalid_from_part(12, 3), 121212);
-        assert_eq!(IdRange::invalid_from_part(99, 3), 999999);
-        assert_eq!(IdRange::invalid_from_part(3859, 3), 385938593859);
+    fn test_u128_bounds() {
+        let near_max = IdRangeNumber::MAX - 1_000_000;
+        let range = IdRange {
+            range: near_max..=IdRangeNumber::MAX,
+        };
+        assert_eq!(range.invalid_ids_sum(2, DECIMAL), Some(0));
+        assert_eq!(range.invalid_ids_any_sum(DECIMAL), Some(0));
+        assert_eq!(
+            range
+                .matching_ids(DigitSet::new([1, 2, 3], DECIMAL).unwrap(), DECIMAL)
+                .count(),
+            0
+        );
+        assert_eq!(Repeated { num_parts: 2 }.next_from(near_max, DECIMAL), None);
+        assert_eq!(
+            Repeated { num_parts: 2 }.next_from(1 << 100, Radix(2)),
+            Some((1 << 101) | (1 << 50))
+        );
+    }
+
+    #[derive(Debug)]
+    enum PatternKind {
+        Palindrome,
+        ZerosAndOnes,
+        Repeated(u32),
+    }
+
+    #[derive(Debug)]
+    struct PatternCase {
+        range: RangeInclusive<IdRangeNumber>,
+        radix: Radix,
+        kind: PatternKind,
+    }
+
+    impl PatternCase {
+        fn generate(rng: &mut impl Rng) -> Self {
+            let start = rng.random_range(0..10_000);
+            let kind = match rng.random_range(0..3) {
+                0 => PatternKind::Palindrome,
+                1 => PatternKind::ZerosAndOnes,
+                _ => PatternKind::Repeated(rng.random_range(2..=3)),
+            };
+            Self {
+                range: start..=start + rng.random_range(0..500),
+                radix: Radix(rng.random_range(2..=16)),
+                kind,
+            }
+        }
+    }
+
+    #[test]
+    fn patterns_match_brute_force() {
+        check_oracle!(
+            PatternCase::generate,
+            |PatternCase { range, radix, kind }: &PatternCase| {
+                let range = IdRange {
+                    range: range.clone(),
+                };
+                match *kind {
+                    PatternKind::Palindrome => range.matching_ids(Palindrome, *radix).collect_vec(),
+                    PatternKind::ZerosAndOnes => range
+                        .matching_ids(DigitSet::new([0, 1], *radix).unwrap(), *radix)
+                        .collect_vec(),
+                    PatternKind::Repeated(num_parts) => range
+                        .matching_ids(Repeated { num_parts }, *radix)
+                        .collect_vec(),
+                }
+            },
+            |PatternCase { range, radix, kind }: &PatternCase| {
+                range
+                    .clone()
+                    .filter(|&id| {
+                        let digits = radix.digits(id);
+                        match *kind {
+                            PatternKind::Palindrome => digits.iter().eq(digits.iter().rev()),
+                            PatternKind::ZerosAndOnes => digits.iter().all(|&d| d <= 1),
+                            PatternKind::Repeated(num_parts) => {
+                                let num_parts = num_parts as usize;
+                                digits.len().is_multiple_of(num_parts)
+                                    && digits.chunks(digits.len() / num_parts).all_equal()
+                            }
+                        }
+                    })
+                    .collect_vec()
+            }
+        );
     }
 }
@@ -1,4 +1,6 @@
 use advent_of_code::{Named, Runner, create_runner, named};
+use itertools::Itertools;
+use num::{BigUint, Zero};
 use std::str::Lines;
 
 fn parse_batteries(line: &str) -> Vec<u8> {
@@ -7,52 +9,153 @@ fn parse_batteries(line: &str) -> Vec<u8> {
         .collect()
 }
 
-fn max_battery_joltage(batteries: &[u8], count: usize) -> Option<u64> {
-    let count = count - 1;
-    let max_digit = batteries[..batteries.len() - count].iter().max()?;
-    if count > 0 {
-        let max_index = batteries.iter().position(|b| b == max_digit)?;
-        max_battery_joltage(&batteries[(max_index + 1)..], count)
-            .map(|joltage| joltage + (*max_digit as u64) * 10u64.pow(count as u32))
-    } else {
-        Some(*max_digit as u64)
+#[derive(Debug, PartialEq)]
+struct BatterySelection {
+    indices: Vec<usize>,
+    joltage: BigUint,
+}
+
+// keeps a stack of chosen batteries, popping smaller ones while there are still
+// enough batteries left to fill the selection
+fn select_batteries(batteries: &[u8], count: usize) -> Result<Vec<usize>, String> {
+    if count == 0 || count > batteries.len() {
+        return Err(format!(
+            "Can't select {} batteries from a bank of {}",
+            count,
+            batteries.len()
+        ));
     }
+    let mut droppable = batteries.len() - count;
+    let mut selected = Vec::with_capacity(batteries.len());
+    for (index, battery) in batteries.iter().enumerate() {
+        while droppable > 0
+            && selected
+                .last()
+                .is_some_and(|&top| batteries[top] < *battery)
+        {
+            selected.pop();
+            droppable -= 1;
+        }
+        selected.push(index);
+    }
+    selected.truncate(count);
+    Ok(selected)
+}
+
+fn max_battery_joltage(batteries: &[u8], count: usize) -> Result<BatterySelection, String> {
+    let indices = select_batteries(batteries, count)?;
+    let joltage = indices
+        .iter()
+        .fold(BigUint::zero(), |joltage, &i| joltage * 10u8 + batteries[i]);
+    Ok(BatterySelection { indices, joltage })
 }
 
-fn part1(input: Lines) -> String {
+fn total_joltage(input: Lines, count: usize) -> Result<String, String> {
     input
         .map(parse_batteries)
-        .map(|batteries| max_battery_joltage(&batteries, 2).unwrap_or(0))
-        .sum::<u64>()
-        .to_string()
+        .map(|batteries| max_battery_joltage(&batteries, count).map(|s| s.joltage))
+        .sum::<Result<BigUint, String>>()
+        .map(|total| total.to_string())
+}
+
+fn part1(input: Lines) -> Result<String, String> {
+    total_joltage(input, 2)
 }
 
-fn part2(input: Lines) -> String {
+fn part2(input: Lines) -> Result<String, String> {
+    total_joltage(input, 12)
+}
+
+// unselected batteries are shown as '.'
+fn highlight_selection(batteries: &[u8], indices: &[usize]) -> String {
+    let mut indices = indices.iter().peekable();
+    batteries
+        .iter()
+        .enumerate()
+        .map(|(i, battery)| {
+            if indices.next_if_eq(&&i).is_some() {
+                char::from(b'0' + battery)
+            } else {
+                '.'
+            }
+        })
+        .collect()
+}
+
+fn bank_report(bank: usize, batteries: &[u8]) -> String {
+    let digits = batteries
+        .iter()
+        .map(|b| char::from(b'0' + b))
+        .collect::<String>();
+    let mut lines = vec![format!("bank {}: {}", bank, digits)];
+    for (part, count) in [(1, 2), (2, 12)] {
+        if let Ok(indices) = select_batteries(batteries, count) {
+            let highlighted = highlight_selection(batteries, &indices);
+            lines.push(format!("  part{}:  {}", part, highlighted));
+        }
+    }
+    let mut previous = BigUint::zero();
+    for count in 1..=batteries.len() {
+        let joltage = max_battery_joltage(batteries, count).unwrap().joltage;
+        lines.push(format!(
+            "  k={:<3} {:>width$} (+{})",
+            count,
+            joltage,
+            &joltage - previous,
+            width = batteries.len()
+        ));
+        previous = joltage;
+    }
+    lines.join("\n")
+}
+
+fn report(input: Lines) -> String {
     input
         .map(parse_batteries)
-        .map(|batteries| max_battery_joltage(&batteries, 12).unwrap_or(0))
-        .sum::<u64>()
-        .to_string()
+        .enumerate()
+        .map(|(i, batteries)| bank_report(i + 1, &batteries))
+        .join("\n")
 }
 
 fn main() {
-    let input = include_str!("input.txt");
     let runner: &Runner = create_runner!();
+    let input = &runner.load(include_str!("input.txt"));
     runner.run(named!(part1), input);
     runner.run(named!(part2), input);
+    runner.run_if_requested(named!(report), input);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use advent_of_code::verify;
+    use advent_of_code::{check_oracle, examples};
+    use rand::Rng;
     use rstest::rstest;
 
+    examples!();
+
     #[test]
-    fn example() {
-        let input = include_str!("example.txt");
-        verify!(part1, input, "357");
-        verify!(part2, input, "3121910778619");
+    fn max_battery_joltage_matches_all_subsets() {
+        check_oracle!(
+            |rng| {
+                let len = rng.random_range(1..=12);
+                let batteries = (0..len).map(|_| rng.random_range(0..=9)).collect_vec();
+                (batteries, rng.random_range(1..=len))
+            },
+            |(batteries, count): &(Vec<u8>, usize)| {
+                max_battery_joltage(batteries, *count)
+                    .ok()
+                    .map(|s| s.joltage)
+            },
+            |(batteries, count): &(Vec<u8>, usize)| {
+                batteries
+                    .iter()
+                    .combinations(*count)
+                    .map(|digits| digits.into_iter().fold(0, |n, &d| n * 10 + d as u64))
+                    .max()
+                    .map(BigUint::from)
+            }
+        );
     }
 
     #[rstest]
@@ -62,7 +165,10 @@ mod tests {
     #[case("818181911112111", 92)]
     fn test_max_battery_joltage_2(#[case] batteries: &str, #[case] expected_joltage: u64) {
         let batteries = parse_batteries(batteries);
-        assert_eq!(max_battery_joltage(&batteries, 2), Some(expected_joltage));
+        assert_eq!(
+            max_battery_joltage(&batteries, 2).map(|s| s.joltage),
+            Ok(BigUint::from(expected_joltage))
+        );
     }
 
     #[rstest]
@@ -72,6 +178,56 @@ mod tests {
     #[case("818181911112111", 888911112111)]
     fn test_max_battery_joltage_12(#[case] batteries: &str, #[case] expected_joltage: u64) {
         let batteries = parse_batteries(batteries);
-        assert_eq!(max_battery_joltage(&batteries, 12), Some(expected_joltage));
+        assert_eq!(
+            max_battery_joltage(&batteries, 12).map(|s| s.joltage),
+            Ok(BigUint::from(expected_joltage))
+        );
+    }
+
+    #[test]
+    fn test_selected_indices() {
+        let batteries = parse_batteries("818181911112111");
+        assert_eq!(
+            max_battery_joltage(&batteries, 2),
+            Ok(BatterySelection {
+                indices: vec![6, 11],
+                joltage: BigUint::from(92u8)
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_count() {
+        let batteries = parse_batteries("12345");
+        assert_eq!(
+            max_battery_joltage(&batteries, 6),
+            Err("Can't select 6 batteries from a bank of 5".to_string())
+        );
+        assert!(max_battery_joltage(&batteries, 0).is_err());
+        assert!(max_battery_joltage(&[], 1).is_err());
+    }
+
+    #[test]
+    fn test_more_digits_than_u64() {
+        let batteries = parse_batteries(&"9".repeat(50));
+        let joltage = max_battery_joltage(&batteries, 40).unwrap().joltage;
+        assert_eq!(joltage.to_string(), "9".repeat(40));
+    }
+
+    #[test]
+    fn test_bank_report() {
+        let batteries = parse_batteries("2342");
+        assert_eq!(
+            bank_report(1, &batteries),
+            [
+                "bank 1: 2342",
+                "  part1:  ..42",
+                "  k=1      4 (+4)",
+                "  k=2     42 (+38)",
+                "  k=3    342 (+300)",
+                "  k=4   2342 (+2000)",
+            ]
+            .join("\n")
+        );
     }
 }
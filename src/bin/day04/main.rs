@@ -1,12 +1,14 @@
+use advent_of_code::input::{InputFormat, Layout};
 use advent_of_code::{Named, Runner, create_runner, named};
 use itertools::Itertools;
-use std::str::Lines;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::iter::{once, repeat_n};
+use std::path::Path;
+use std::str::{FromStr, Lines};
 
-struct Grid {
-    rows: Vec<Vec<bool>>,
-}
-
-const NEIGHBORS: &[(isize, isize)] = &[
+const MOORE: &[(isize, isize)] = &[
     (-1, -1),
     (0, -1),
     (1, -1),
@@ -17,35 +19,208 @@ const NEIGHBORS: &[(isize, isize)] = &[
     (1, 1),
 ];
 
+const VON_NEUMANN: &[(isize, isize)] = &[(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+#[derive(Clone, Debug)]
+enum Neighborhood {
+    VonNeumann,
+    Moore,
+    Custom(Vec<(isize, isize)>),
+}
+
+impl Neighborhood {
+    fn offsets(&self) -> &[(isize, isize)] {
+        match self {
+            Neighborhood::VonNeumann => VON_NEUMANN,
+            Neighborhood::Moore => MOORE,
+            Neighborhood::Custom(offsets) => offsets,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Threshold {
+    Below(usize),
+    AtMost(usize),
+    AtLeast(usize),
+    Above(usize),
+}
+
+impl Threshold {
+    fn accepts(&self, neighbor_rolls: usize) -> bool {
+        match *self {
+            Threshold::Below(n) => neighbor_rolls < n,
+            Threshold::AtMost(n) => neighbor_rolls <= n,
+            Threshold::AtLeast(n) => neighbor_rolls >= n,
+            Threshold::Above(n) => neighbor_rolls > n,
+        }
+    }
+}
+
+// e.g. "<4" or ">=2"
+impl FromStr for Threshold {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (threshold, n): (fn(usize) -> Threshold, _) = if let Some(n) = s.strip_prefix("<=") {
+            (Threshold::AtMost, n)
+        } else if let Some(n) = s.strip_prefix(">=") {
+            (Threshold::AtLeast, n)
+        } else if let Some(n) = s.strip_prefix('<') {
+            (Threshold::Below, n)
+        } else if let Some(n) = s.strip_prefix('>') {
+            (Threshold::Above, n)
+        } else {
+            return Err(format!("expected a threshold such as <4, got '{}'", s));
+        };
+        n.parse()
+            .map(threshold)
+            .map_err(|e| format!("invalid threshold '{}': {}", s, e))
+    }
+}
+
+// a roll is accessible when the number of rolls in its neighborhood passes the threshold
+#[derive(Clone, Debug)]
+struct Rule {
+    roll: char,
+    neighborhood: Neighborhood,
+    threshold: Threshold,
+    wraps: bool,
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self {
+            roll: '@',
+            neighborhood: Neighborhood::Moore,
+            threshold: Threshold::Below(4),
+            wraps: false,
+        }
+    }
+}
+
+// e.g. "moore <4", "von-neumann >=2 wrap" or "1,0;0,-1 <=1", where a custom neighborhood is a
+// list of dx,dy offsets separated by semicolons
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let neighborhood = match words.next() {
+            Some("moore") => Neighborhood::Moore,
+            Some("von-neumann") => Neighborhood::VonNeumann,
+            Some(word) if !word.contains(',') => {
+                return Err(format!(
+                    "expected moore, von-neumann or a list of offsets, got '{}'",
+                    word
+                ));
+            }
+            Some(offsets) => Neighborhood::Custom(
+                offsets
+                    .split(';')
+                    .map(|offset| {
+                        offset
+                            .split_once(',')
+                            .and_then(|(dx, dy)| Some((dx.parse().ok()?, dy.parse().ok()?)))
+                            .ok_or_else(|| format!("invalid offset '{}'", offset))
+                    })
+                    .try_collect()?,
+            ),
+            None => return Err("expected a neighborhood".to_string()),
+        };
+        let threshold = words
+            .next()
+            .ok_or("expected a threshold after the neighborhood")?
+            .parse()?;
+        let wraps = match words.next() {
+            None => false,
+            Some("wrap") => true,
+            Some(word) => return Err(format!("expected 'wrap', got '{}'", word)),
+        };
+        if let Some(word) = words.next() {
+            return Err(format!("unexpected '{}' after 'wrap'", word));
+        }
+        Ok(Rule {
+            neighborhood,
+            threshold,
+            wraps,
+            ..Rule::default()
+        })
+    }
+}
+
+// the reports use $ROLL_RULE in place of the puzzle's rule when it's set
+fn rule_from_env() -> Result<Rule, String> {
+    match std::env::var("ROLL_RULE") {
+        Ok(rule) => rule.parse().map_err(|e| format!("ROLL_RULE: {}", e)),
+        Err(_) => Ok(Rule::default()),
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Grid {
+    rows: Vec<Vec<bool>>,
+    rule: Rule,
+}
+
 impl Grid {
     fn parse(input: Lines) -> Self {
+        Self::parse_with_rule(input, Rule::default())
+    }
+
+    fn parse_with_rule(input: Lines, rule: Rule) -> Self {
         let rows = input
-            .map(|line| line.chars().map(|c| c == '@').collect_vec())
+            .map(|line| line.chars().map(|c| c == rule.roll).collect_vec())
             .collect();
-        Self { rows }
+        Self { rows, rule }
     }
 
-    fn get(&self, x: isize, y: isize) -> Option<bool> {
-        if x >= 0 && y >= 0 {
+    fn offset(&self, x: usize, y: usize, dx: isize, dy: isize) -> Option<(usize, usize)> {
+        let (nx, ny) = (x as isize + dx, y as isize + dy);
+        if self.rule.wraps {
+            // every row wraps at the widest row's width rather than its own, so on a ragged
+            // grid each cell is still watched by exactly the cells it neighbors
+            let width = self.rows.iter().map(Vec::len).max().unwrap_or(0) as isize;
+            let nx = nx.checked_rem_euclid(width)? as usize;
+            let ny = ny.rem_euclid(self.rows.len() as isize) as usize;
+            self.rows[ny].get(nx).map(|_| (nx, ny))
+        } else if nx >= 0 && ny >= 0 {
+            let (nx, ny) = (nx as usize, ny as usize);
             self.rows
-                .get(y as usize)
-                .and_then(|row| row.get(x as usize).copied())
+                .get(ny)
+                .and_then(|row| row.get(nx))
+                .map(|_| (nx, ny))
         } else {
             None
         }
     }
 
-    fn neighbor_rolls(&self, x: usize, y: usize) -> usize {
-        NEIGHBORS
+    fn neighbors(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        let offsets = self.rule.neighborhood.offsets();
+        offsets
             .iter()
-            .filter(|(nx, ny)| self.get(x as isize + nx, y as isize + ny) == Some(true))
+            .filter_map(move |&(dx, dy)| self.offset(x, y, dx, dy))
+    }
+
+    // cells which have (x, y) in their neighborhood, which differ from its neighbors
+    // when a custom neighborhood isn't symmetric
+    fn watchers(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        let offsets = self.rule.neighborhood.offsets();
+        offsets
+            .iter()
+            .filter_map(move |&(dx, dy)| self.offset(x, y, -dx, -dy))
+    }
+
+    fn neighbor_rolls(&self, x: usize, y: usize) -> usize {
+        self.neighbors(x, y)
+            .filter(|&(nx, ny)| self.rows[ny][nx])
             .count()
     }
 
     fn accessible_rolls(&self) -> impl Iterator<Item = (usize, usize)> {
         self.rows.iter().enumerate().flat_map(move |(y, row)| {
             row.iter().enumerate().filter_map(move |(x, is_roll)| {
-                if *is_roll && self.neighbor_rolls(x, y) < 4 {
+                if *is_roll && self.rule.threshold.accepts(self.neighbor_rolls(x, y)) {
                     Some((x, y))
                 } else {
                     None
@@ -54,20 +229,222 @@ impl Grid {
         })
     }
 
-    fn remove_accessible(&mut self) -> usize {
-        let accessible = self.accessible_rolls().collect_vec();
-        let accessible_count = accessible.len();
-        accessible.into_iter().for_each(|(x, y)| {
-            self.rows[y][x] = false;
-        });
-        accessible_count
+    // only rolls watching a removed roll can change, so each round checks just those
+    // instead of rescanning the grid
+    fn remove_all_accessible<F: FnMut(usize, (usize, usize))>(
+        &mut self,
+        mut on_removed: F,
+    ) -> usize {
+        let mut neighbor_counts = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(y, row)| {
+                (0..row.len())
+                    .map(|x| self.neighbor_rolls(x, y))
+                    .collect_vec()
+            })
+            .collect_vec();
+        let mut frontier = self.accessible_rolls().collect_vec();
+        let mut removed = 0;
+        let mut round = 0;
+        while !frontier.is_empty() {
+            for &(x, y) in frontier.iter() {
+                self.rows[y][x] = false;
+                on_removed(round, (x, y));
+            }
+            removed += frontier.len();
+            let mut changed = Vec::new();
+            for (x, y) in frontier {
+                for (wx, wy) in self.watchers(x, y) {
+                    neighbor_counts[wy][wx] -= 1;
+                    changed.push((wx, wy));
+                }
+            }
+            // checked after all of the round's removals since thresholds like AtLeast
+            // can stop accepting a roll as its count keeps dropping
+            frontier = changed
+                .into_iter()
+                .unique()
+                .filter(|&(x, y)| {
+                    self.rows[y][x] && self.rule.threshold.accepts(neighbor_counts[y][x])
+                })
+                .collect_vec();
+            round += 1;
+        }
+        removed
     }
 
     fn total_accessible_rolls(&mut self) -> usize {
-        std::iter::from_fn(|| Some(self.remove_accessible()).filter(|c| *c > 0)).sum()
+        self.remove_all_accessible(|_, _| {})
+    }
+
+    fn removal_rounds(&mut self) -> Vec<Vec<Option<usize>>> {
+        let mut rounds = self
+            .rows
+            .iter()
+            .map(|row| vec![None; row.len()])
+            .collect_vec();
+        self.remove_all_accessible(|round, (x, y)| rounds[y][x] = Some(round));
+        rounds
+    }
+
+    // one frame per round showing the rolls removed in that round
+    fn removal_frames(&mut self) -> Vec<Frame> {
+        let initial = self.rows.clone();
+        let rounds = self.removal_rounds();
+        let num_rounds = rounds.iter().flatten().flatten().max().map_or(0, |r| r + 1);
+        (0..num_rounds)
+            .map(|round| {
+                initial
+                    .iter()
+                    .zip(rounds.iter())
+                    .map(|(row, row_rounds)| {
+                        row.iter()
+                            .zip(row_rounds)
+                            .map(|(&is_roll, removed)| match removed {
+                                Some(r) if *r == round => FrameCell::Removed,
+                                Some(r) if *r < round => FrameCell::Empty,
+                                _ if is_roll => FrameCell::Roll,
+                                _ => FrameCell::Empty,
+                            })
+                            .collect_vec()
+                    })
+                    .collect_vec()
+            })
+            .collect()
     }
 }
 
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows.iter() {
+            for &is_roll in row {
+                write!(f, "{}", if is_roll { self.rule.roll } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum FrameCell {
+    Empty,
+    Roll,
+    Removed,
+}
+
+impl FrameCell {
+    fn as_char(&self, roll: char) -> char {
+        match self {
+            FrameCell::Empty => '.',
+            FrameCell::Roll => roll,
+            FrameCell::Removed => 'x',
+        }
+    }
+
+    fn as_rgb(&self) -> [u8; 3] {
+        match self {
+            FrameCell::Empty => [32, 32, 32],
+            FrameCell::Roll => [230, 230, 230],
+            FrameCell::Removed => [220, 40, 40],
+        }
+    }
+}
+
+type Frame = Vec<Vec<FrameCell>>;
+
+fn render_frame(frame: &Frame, roll: char) -> String {
+    let removed = frame
+        .iter()
+        .flatten()
+        .filter(|c| **c == FrameCell::Removed)
+        .count();
+    let rows = frame.iter().map(|row| {
+        row.iter()
+            .map(|cell| cell.as_char(roll))
+            .collect::<String>()
+    });
+    once(format!("Remove {} rolls of paper:", removed))
+        .chain(rows)
+        .join("\n")
+}
+
+const PPM_CELL_SIZE: usize = 4;
+
+// binary PPM, each cell is drawn as a PPM_CELL_SIZE square
+fn encode_ppm(frame: &Frame) -> Vec<u8> {
+    let width = frame.iter().map(|row| row.len()).max().unwrap_or(0) * PPM_CELL_SIZE;
+    let height = frame.len() * PPM_CELL_SIZE;
+    let mut ppm = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+    for row in frame {
+        let line = (0..width / PPM_CELL_SIZE)
+            .map(|x| row.get(x).copied().unwrap_or(FrameCell::Empty))
+            .flat_map(|cell| repeat_n(cell.as_rgb(), PPM_CELL_SIZE))
+            .flatten()
+            .collect_vec();
+        for _ in 0..PPM_CELL_SIZE {
+            ppm.extend_from_slice(&line);
+        }
+    }
+    ppm
+}
+
+fn write_ppm_frames(frames: &[Frame], dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    for (round, frame) in frames.iter().enumerate() {
+        fs::write(
+            dir.join(format!("round_{:04}.ppm", round)),
+            encode_ppm(frame),
+        )?;
+    }
+    Ok(())
+}
+
+// each removed roll shows the round it was removed in, rolls which are never removed are unchanged
+fn render_rounds(grid: &Grid, rounds: &[Vec<Option<usize>>]) -> String {
+    grid.rows
+        .iter()
+        .zip(rounds)
+        .map(|(row, row_rounds)| {
+            row.iter()
+                .zip(row_rounds)
+                .map(|(is_roll, round)| match (is_roll, round) {
+                    (_, Some(round)) => char::from_digit(*round as u32 % 36, 36).unwrap(),
+                    (true, None) => grid.rule.roll,
+                    (false, None) => '.',
+                })
+                .collect::<String>()
+        })
+        .join("\n")
+}
+
+fn rounds(input: Lines) -> Result<String, String> {
+    let mut grid = Grid::parse_with_rule(input, rule_from_env()?);
+    let removal_rounds = grid.removal_rounds();
+    Ok(render_rounds(&grid, &removal_rounds))
+}
+
+fn frames(input: Lines) -> Result<String, String> {
+    let mut grid = Grid::parse_with_rule(input, rule_from_env()?);
+    let roll = grid.rule.roll;
+    Ok(grid
+        .removal_frames()
+        .iter()
+        .map(|frame| render_frame(frame, roll))
+        .join("\n\n"))
+}
+
+// writes to $FRAMES_DIR, or target/day04_frames by default
+fn ppm_frames(input: Lines) -> Result<String, String> {
+    let dir = std::env::var("FRAMES_DIR").unwrap_or_else(|_| "target/day04_frames".to_string());
+    let frames = Grid::parse_with_rule(input, rule_from_env()?).removal_frames();
+    write_ppm_frames(&frames, Path::new(&dir))
+        .map(|()| format!("wrote {} frames to {}", frames.len(), dir))
+        .map_err(|e| format!("failed to write frames to {}: {}", dir, e))
+}
+
 fn part1(input: Lines) -> String {
     Grid::parse(input).accessible_rolls().count().to_string()
 }
@@ -76,22 +453,242 @@ fn part2(input: Lines) -> String {
     Grid::parse(input).total_accessible_rolls().to_string()
 }
 
+// a short row just has fewer rolls, so a ragged grid is reported rather than padded out
+const INPUT_FORMAT: InputFormat = InputFormat::new(Layout::Rectangular { padding: None });
+
 fn main() {
-    let input = include_str!("input.txt");
-    let runner: &Runner = create_runner!();
+    let runner: &Runner = create_runner!(INPUT_FORMAT);
+    let input = &runner.load(include_str!("input.txt"));
     runner.run(named!(part1), input);
     runner.run(named!(part2), input);
+    runner.run_if_requested(named!(rounds), input);
+    runner.run_if_requested(named!(frames), input);
+    runner.run_if_requested(named!(ppm_frames), input);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use advent_of_code::verify;
+    use advent_of_code::{check_oracle, examples};
+    use rand::Rng;
+
+    examples!();
+
+    fn remove_by_rescanning(grid: &mut Grid) -> usize {
+        let mut removed = 0;
+        loop {
+            let accessible = grid.accessible_rolls().collect_vec();
+            if accessible.is_empty() {
+                return removed;
+            }
+            removed += accessible.len();
+            for (x, y) in accessible {
+                grid.rows[y][x] = false;
+            }
+        }
+    }
+
+    #[test]
+    fn example_rounds() {
+        let input = include_str!("example.txt");
+        assert_eq!(
+            rounds(input.lines()),
+            Ok([
+                "..00.0010.",
+                "023.1.1.21",
+                "13467.0.22",
+                "1.58@@..1.",
+                "02.@@@@.10",
+                ".13@@@@@.1",
+                ".1.@.@.@@2",
+                "0.3@@.@@@3",
+                ".12@@@@@4.",
+                "0.0.@@@.0.",
+            ]
+            .join("\n"))
+        );
+    }
 
     #[test]
-    fn example() {
+    fn test_parse_rule() {
         let input = include_str!("example.txt");
-        verify!(part1, input, "13");
-        verify!(part2, input, "43");
+        let accessible = |rule: &str| {
+            Grid::parse_with_rule(input.lines(), rule.parse().unwrap())
+                .accessible_rolls()
+                .count()
+        };
+        assert_eq!(accessible("moore <4"), 13);
+        assert_eq!(
+            accessible("moore <4"),
+            accessible("-1,-1;0,-1;1,-1;-1,0;1,0;-1,1;0,1;1,1 <=3")
+        );
+        let rule = "von-neumann >=2 wrap".parse::<Rule>().unwrap();
+        assert!(matches!(rule.neighborhood, Neighborhood::VonNeumann));
+        assert!(matches!(rule.threshold, Threshold::AtLeast(2)));
+        assert!(rule.wraps);
+        let error = |rule: &str| rule.parse::<Rule>().err().unwrap();
+        assert_eq!(
+            error("moore"),
+            "expected a threshold after the neighborhood"
+        );
+        assert_eq!(
+            error("moore =4"),
+            "expected a threshold such as <4, got '=4'"
+        );
+        assert_eq!(error("1,0;2 <4"), "invalid offset '2'");
+        assert_eq!(
+            error("hex <4"),
+            "expected moore, von-neumann or a list of offsets, got 'hex'"
+        );
+        assert_eq!(error("moore <4 round"), "expected 'wrap', got 'round'");
+    }
+
+    #[test]
+    fn example_frames() {
+        let input = include_str!("example.txt");
+        let mut grid = Grid::parse(input.lines());
+        assert_eq!(grid.to_string(), input);
+        let frames = grid.removal_frames();
+        assert_eq!(
+            render_frame(&frames[0], '@'),
+            [
+                "Remove 13 rolls of paper:",
+                "..xx.xx@x.",
+                "x@@.@.@.@@",
+                "@@@@@.x.@@",
+                "@.@@@@..@.",
+                "x@.@@@@.@x",
+                ".@@@@@@@.@",
+                ".@.@.@.@@@",
+                "x.@@@.@@@@",
+                ".@@@@@@@@.",
+                "x.x.@@@.x.",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            render_frame(&frames[1], '@'),
+            [
+                "Remove 12 rolls of paper:",
+                ".......x..",
+                ".@@.x.x.@x",
+                "x@@@@...@@",
+                "x.@@@@..x.",
+                ".@.@@@@.x.",
+                ".x@@@@@@.x",
+                ".x.@.@.@@@",
+                "..@@@.@@@@",
+                ".x@@@@@@@.",
+                "....@@@...",
+            ]
+            .join("\n")
+        );
+        let removed = frames
+            .iter()
+            .flatten()
+            .flatten()
+            .filter(|c| **c == FrameCell::Removed)
+            .count();
+        assert_eq!(removed, 43);
+    }
+
+    #[test]
+    fn test_encode_ppm() {
+        let frame = vec![vec![FrameCell::Roll, FrameCell::Removed]];
+        let ppm = encode_ppm(&frame);
+        let header = b"P6\n8 4\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        assert_eq!(ppm.len(), header.len() + 8 * 4 * 3);
+        assert_eq!(
+            &ppm[header.len()..header.len() + 3],
+            FrameCell::Roll.as_rgb()
+        );
+        assert_eq!(
+            &ppm[header.len() + 12..header.len() + 15],
+            FrameCell::Removed.as_rgb()
+        );
+    }
+
+    #[test]
+    fn test_wrapping_rule() {
+        let input = "@@@\n@@@\n@@@";
+        assert_eq!(Grid::parse(input.lines()).total_accessible_rolls(), 9);
+        let rule = Rule {
+            wraps: true,
+            ..Rule::default()
+        };
+        let mut grid = Grid::parse_with_rule(input.lines(), rule);
+        assert_eq!(grid.total_accessible_rolls(), 0);
+    }
+
+    #[test]
+    fn test_von_neumann_rule() {
+        let rule = Rule {
+            roll: '#',
+            neighborhood: Neighborhood::VonNeumann,
+            threshold: Threshold::AtMost(1),
+            wraps: false,
+        };
+        let grid = Grid::parse_with_rule("#.#\n###\n.#.".lines(), rule);
+        assert_eq!(
+            grid.accessible_rolls().collect_vec(),
+            [(0, 0), (2, 0), (1, 2)]
+        );
+    }
+
+    // the last row can be short, as in a ragged input
+    fn random_grid(rng: &mut impl Rng) -> Grid {
+        let width = rng.random_range(1..12);
+        let rows = (0..rng.random_range(1..width * 12))
+            .map(|_| rng.random_bool(0.7))
+            .chunks(width)
+            .into_iter()
+            .map(Iterator::collect)
+            .collect();
+        let neighborhood = match rng.random_range(0..3) {
+            0 => Neighborhood::Moore,
+            1 => Neighborhood::VonNeumann,
+            _ => Neighborhood::Custom(vec![(1, 0), (2, 1), (0, -1), (1, 0)]),
+        };
+        let n = rng.random_range(0..6);
+        let threshold = match rng.random_range(0..4) {
+            0 => Threshold::Below(n),
+            1 => Threshold::AtMost(n),
+            2 => Threshold::AtLeast(n),
+            _ => Threshold::Above(n),
+        };
+        let rule = Rule {
+            roll: '@',
+            neighborhood,
+            threshold,
+            wraps: rng.random_bool(0.5),
+        };
+        Grid { rows, rule }
+    }
+
+    #[test]
+    fn test_wrapping_ragged_grid() {
+        let rule = "moore >=0 wrap".parse().unwrap();
+        let mut grid = Grid::parse_with_rule("@@@\n@".lines(), rule);
+        assert_eq!(grid.neighbor_rolls(0, 1), 6);
+        assert_eq!(grid.neighbor_rolls(1, 0), 4);
+        assert_eq!(grid.total_accessible_rolls(), 4);
+    }
+
+    #[test]
+    fn removal_matches_rescanning() {
+        check_oracle!(
+            random_grid,
+            |grid: &Grid| {
+                let mut grid = grid.clone();
+                let removed = grid.total_accessible_rolls();
+                (removed, grid.rows)
+            },
+            |grid: &Grid| {
+                let mut grid = grid.clone();
+                let removed = remove_by_rescanning(&mut grid);
+                (removed, grid.rows)
+            }
+        );
     }
 }
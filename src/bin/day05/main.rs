@@ -1,30 +1,70 @@
+use advent_of_code::parse::{self, ParseError, Span};
 use advent_of_code::{Named, Runner, create_runner, named};
 use itertools::Itertools;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 use std::ops::RangeInclusive;
 use std::str::Lines;
 
-fn parse_input(mut input: Lines) -> (Vec<RangeInclusive<u64>>, Vec<u64>) {
-    let ranges = input
-        .by_ref()
-        .take_while(|line| !line.is_empty())
-        .map(|line| {
-            let (start, end) = line.split_once('-').unwrap();
-            let start: u64 = start.parse().unwrap();
-            let end: u64 = end.parse().unwrap();
-            start..=end
-        })
-        .collect_vec();
-    let numbers = input.map(|line| line.parse::<u64>().unwrap()).collect_vec();
-    (ranges, numbers)
+fn parse_range(line: Span) -> Result<RangeInclusive<u64>, ParseError> {
+    let Ok((start, end)) = line.split_once("-") else {
+        return Err(line.error(if line.text.parse::<u64>().is_ok() {
+            format!(
+                "expected a range 'start-end' but found ID '{}', is the blank line missing?",
+                line.text
+            )
+        } else {
+            format!("expected a range 'start-end' but found '{}'", line.text)
+        }));
+    };
+    let bound = |bound: Span, name| {
+        bound
+            .text
+            .parse::<u64>()
+            .map_err(|e| bound.error(format!("invalid range {} '{}': {}", name, bound.text, e)))
+    };
+    let (start, end) = (bound(start, "start")?, bound(end, "end")?);
+    if start > end {
+        Err(line.error(format!("range {}-{} is reversed", start, end)))
+    } else {
+        Ok(start..=end)
+    }
+}
+
+fn parse_input(input: Lines) -> Result<(Vec<RangeInclusive<u64>>, Vec<u64>), ParseError> {
+    let mut ranges = Vec::new();
+    let mut numbers = None;
+    let mut num_lines = 0;
+    for line in parse::lines(input) {
+        num_lines = line.line;
+        if line.text.trim() != line.text {
+            return Err(line.error(format!("unexpected whitespace in '{}'", line.text)));
+        }
+        match (&mut numbers, line.text.is_empty()) {
+            (None, true) => numbers = Some(Vec::new()),
+            (None, false) => ranges.push(parse_range(line)?),
+            (Some(_), true) => return Err(line.error("unexpected blank line in IDs")),
+            (Some(numbers), false) => numbers.push(
+                line.text
+                    .parse::<u64>()
+                    .map_err(|e| line.error(format!("invalid ID '{}': {}", line.text, e)))?,
+            ),
+        }
+    }
+    let numbers = numbers.ok_or_else(|| {
+        Span::new("", num_lines + 1).error("missing blank line between ranges and IDs")
+    })?;
+    Ok((ranges, numbers))
 }
 
-fn part1(input: Lines) -> String {
-    let (ranges, numbers) = parse_input(input);
-    numbers
+fn part1(input: Lines) -> Result<String, String> {
+    let (ranges, numbers) = parse_input(input)?;
+    let merged = merge_ranges(ranges);
+    Ok(numbers
         .into_iter()
-        .filter(|num| ranges.iter().any(|range| range.contains(num)))
+        .filter(|&num| merged_contains(&merged, num))
         .count()
-        .to_string()
+        .to_string())
 }
 
 fn merge_ranges(mut ranges: Vec<RangeInclusive<u64>>) -> Vec<RangeInclusive<u64>> {
@@ -35,7 +75,7 @@ fn merge_ranges(mut ranges: Vec<RangeInclusive<u64>>) -> Vec<RangeInclusive<u64>
         // we'd still get the right answer without merging these, but might as well
         // to have one less range in the output
         if let Some(last) = merged.last_mut()
-            && *range.start() <= last.end() + 1
+            && *range.start() <= last.end().saturating_add(1)
         {
             *last = *last.start()..=*last.end().max(range.end());
         } else {
@@ -45,31 +85,347 @@ fn merge_ranges(mut ranges: Vec<RangeInclusive<u64>>) -> Vec<RangeInclusive<u64>
     merged
 }
 
-fn part2(input: Lines) -> String {
-    let (ranges, _numbers) = parse_input(input);
-    merge_ranges(ranges)
+// merged ranges are sorted and disjoint, so the only candidate is the first one ending at or after num
+fn merged_contains(merged: &[RangeInclusive<u64>], num: u64) -> bool {
+    let index = merged.partition_point(|range| *range.end() < num);
+    merged.get(index).is_some_and(|range| range.contains(&num))
+}
+
+// indices of the original ranges containing each number, found by sweeping over the numbers in
+// order while keeping a heap of the ranges which have started, ordered by their end
+fn containing_ranges(ranges: &[RangeInclusive<u64>], numbers: &[u64]) -> Vec<Vec<usize>> {
+    let mut by_start = (0..ranges.len())
+        .sorted_by_key(|&i| *ranges[i].start())
+        .peekable();
+    let mut active = BinaryHeap::new();
+    let mut containing = vec![Vec::new(); numbers.len()];
+    for query in (0..numbers.len()).sorted_by_key(|&i| numbers[i]) {
+        let num = numbers[query];
+        while let Some(i) = by_start.next_if(|&i| *ranges[i].start() <= num) {
+            active.push(Reverse((*ranges[i].end(), i)));
+        }
+        while active.peek().is_some_and(|Reverse((end, _))| *end < num) {
+            active.pop();
+        }
+        containing[query] = active.iter().map(|Reverse((_, i))| *i).sorted().collect();
+    }
+    containing
+}
+
+fn containing(input: Lines) -> Result<String, String> {
+    let (ranges, numbers) = parse_input(input)?;
+    let containing = containing_ranges(&ranges, &numbers)
         .into_iter()
-        .map(|range| range.count())
-        .sum::<usize>()
-        .to_string()
+        .zip(numbers)
+        .map(|(indices, num)| {
+            if indices.is_empty() {
+                format!("{}: spoiled", num)
+            } else {
+                let found = indices
+                    .into_iter()
+                    .map(|i| format!("{}-{}", ranges[i].start(), ranges[i].end()))
+                    .join(", ");
+                format!("{}: fresh in {}", num, found)
+            }
+        })
+        .join("\n");
+    Ok(containing)
+}
+
+// the IDs within bounds not covered by any of the merged ranges
+fn complement(
+    merged: &[RangeInclusive<u64>],
+    bounds: RangeInclusive<u64>,
+) -> Vec<RangeInclusive<u64>> {
+    let mut gaps = Vec::new();
+    // None once the ranges reach u64::MAX
+    let mut next = Some(*bounds.start());
+    for range in merged {
+        let Some(start) = next else { break };
+        if range.start() > bounds.end() {
+            break;
+        }
+        if *range.start() > start {
+            gaps.push(start..=range.start() - 1);
+        }
+        if *range.end() >= start {
+            next = range.end().checked_add(1);
+        }
+    }
+    if let Some(start) = next
+        && start <= *bounds.end()
+    {
+        gaps.push(start..=*bounds.end());
+    }
+    gaps
+}
+
+fn range_len(range: &RangeInclusive<u64>) -> u128 {
+    (range.end() - range.start()) as u128 + 1
+}
+
+// the first of the largest when there's a tie
+fn largest_gap(gaps: &[RangeInclusive<u64>]) -> Option<&RangeInclusive<u64>> {
+    gaps.iter()
+        .rev()
+        .max_by_key(|gap| *gap.end() - *gap.start())
+}
+
+fn count_in_gaps(gaps: &[RangeInclusive<u64>], numbers: &[u64]) -> Vec<usize> {
+    let numbers = numbers.iter().copied().sorted().collect_vec();
+    gaps.iter()
+        .map(|gap| {
+            numbers.partition_point(|num| num <= gap.end())
+                - numbers.partition_point(|num| num < gap.start())
+        })
+        .collect()
+}
+
+// the bounds default to the span of the ranges, and can be set with e.g. GAP_BOUNDS=0-100
+fn gaps(input: Lines) -> Result<String, String> {
+    let (ranges, numbers) = parse_input(input)?;
+    let merged = merge_ranges(ranges);
+    let bounds = match std::env::var("GAP_BOUNDS") {
+        Ok(bounds) => parse_range(Span::new(&bounds, 1))
+            .map_err(|e| format!("GAP_BOUNDS column {}: {}", e.column, e.message))?,
+        Err(_) => match (merged.first(), merged.last()) {
+            (Some(first), Some(last)) => *first.start()..=*last.end(),
+            _ => return Err("no ranges to find gaps between".to_string()),
+        },
+    };
+    let gaps = complement(&merged, bounds.clone());
+    let mut lines = vec![format!("gaps in {}-{}:", bounds.start(), bounds.end())];
+    for (gap, count) in gaps.iter().zip(count_in_gaps(&gaps, &numbers)) {
+        lines.push(format!(
+            "  {}-{}: {} IDs, {} spoiled",
+            gap.start(),
+            gap.end(),
+            range_len(gap),
+            count
+        ));
+    }
+    if let Some(gap) = largest_gap(&gaps) {
+        lines.push(format!(
+            "largest gap: {}-{} ({} IDs)",
+            gap.start(),
+            gap.end(),
+            range_len(gap)
+        ));
+    }
+    let uncovered = gaps.iter().map(range_len).sum::<u128>();
+    lines.push(format!("uncovered: {} IDs", uncovered));
+    Ok(lines.join("\n"))
+}
+
+fn part2(input: Lines) -> Result<String, String> {
+    let (ranges, _numbers) = parse_input(input)?;
+    Ok(merge_ranges(ranges)
+        .into_iter()
+        .map(|range| range_len(&range))
+        .sum::<u128>()
+        .to_string())
 }
 
 fn main() {
-    let input = include_str!("input.txt");
     let runner: &Runner = create_runner!();
+    let input = &runner.load(include_str!("input.txt"));
     runner.run(named!(part1), input);
     runner.run(named!(part2), input);
+    runner.run_if_requested(named!(containing), input);
+    runner.run_if_requested(named!(gaps), input);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use advent_of_code::verify;
+    use advent_of_code::{check_oracle, examples, verify};
+    use rand::Rng;
+    use rstest::rstest;
+    use std::collections::HashSet;
+
+    type RangesAndNumbers = (Vec<RangeInclusive<u64>>, Vec<u64>);
+
+    // up to 10 short ranges and 20 numbers, with some numbers outside every range
+    fn random_ranges(rng: &mut impl Rng) -> RangesAndNumbers {
+        let num_ranges = rng.random_range(0..10);
+        let ranges = (0..num_ranges)
+            .map(|_| {
+                let start = rng.random_range(0..100);
+                start..=start + rng.random_range(0..20)
+            })
+            .collect_vec();
+        let num_numbers = rng.random_range(0..20);
+        let numbers = (0..num_numbers)
+            .map(|_| rng.random_range(0..130))
+            .collect_vec();
+        (ranges, numbers)
+    }
+
+    examples!();
 
     #[test]
-    fn example() {
+    fn example_containing() {
+        let input = include_str!("example.txt");
+        assert_eq!(
+            containing(input.lines()).unwrap(),
+            [
+                "1: spoiled",
+                "5: fresh in 3-5",
+                "8: spoiled",
+                "11: fresh in 10-14",
+                "17: fresh in 16-20, 12-18",
+                "32: spoiled",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[rstest]
+    #[case(1..=32, vec![1..=2, 6..=9, 21..=32], vec![1, 1, 1])]
+    #[case(3..=20, vec![6..=9], vec![1])]
+    #[case(7..=12, vec![7..=9], vec![1])]
+    #[case(11..=17, vec![], vec![])]
+    #[case(40..=50, vec![40..=50], vec![0])]
+    fn test_example_gaps(
+        #[case] bounds: RangeInclusive<u64>,
+        #[case] expected_gaps: Vec<RangeInclusive<u64>>,
+        #[case] expected_counts: Vec<usize>,
+    ) {
         let input = include_str!("example.txt");
-        verify!(part1, input, "3");
-        verify!(part2, input, "14");
+        let (ranges, numbers) = parse_input(input.lines()).unwrap();
+        let gaps = complement(&merge_ranges(ranges), bounds);
+        assert_eq!(gaps, expected_gaps);
+        assert_eq!(count_in_gaps(&gaps, &numbers), expected_counts);
+    }
+
+    #[test]
+    fn test_largest_gap() {
+        assert_eq!(largest_gap(&[1..=2, 6..=9, 21..=24]), Some(&(6..=9)));
+        assert_eq!(largest_gap(&[]), None);
+    }
+
+    #[test]
+    fn test_complement_at_limits() {
+        let merged = merge_ranges(vec![0..=5, 10..=u64::MAX, 20..=30]);
+        assert_eq!(complement(&merged, 0..=u64::MAX), vec![6..=9]);
+        assert_eq!(complement(&[], 0..=u64::MAX), vec![0..=u64::MAX]);
+        assert_eq!(range_len(&(0..=u64::MAX)), 1 << 64);
+    }
+
+    #[rstest]
+    #[case("3-5\n10-14\n\n1", "8")]
+    #[case("0-18446744073709551615\n\n1", "18446744073709551616")]
+    #[case("0-5\n10-18446744073709551615\n3-7\n\n1", "18446744073709551614")]
+    fn test_part2_counts_up_to_u64_max(#[case] input: &str, #[case] expected: &str) {
+        verify!(part2, input, expected);
+    }
+
+    #[test]
+    fn complement_matches_expanded_set() {
+        check_oracle!(
+            |rng| {
+                let (ranges, _) = random_ranges(rng);
+                (ranges, rng.random_range(0..130), rng.random_range(0..130))
+            },
+            |(ranges, start, end): &(Vec<RangeInclusive<u64>>, u64, u64)| {
+                complement(&merge_ranges(ranges.clone()), *start..=*end)
+                    .into_iter()
+                    .flatten()
+                    .collect_vec()
+            },
+            |(ranges, start, end): &(Vec<RangeInclusive<u64>>, u64, u64)| {
+                let expanded: HashSet<u64> = ranges.iter().cloned().flatten().collect();
+                (*start..=*end)
+                    .filter(|num| !expanded.contains(num))
+                    .collect_vec()
+            }
+        );
+    }
+
+    #[rstest]
+    #[case(
+        "3-5\n1",
+        "line 2, column 1: expected a range 'start-end' but found ID '1', is the blank line missing?"
+    )]
+    #[case(
+        "3-5\n10-14",
+        "line 3, column 1: missing blank line between ranges and IDs"
+    )]
+    #[case("3-5\n14-10\n\n1", "line 2, column 1: range 14-10 is reversed")]
+    #[case(
+        "3-5\n10 - 14\n\n1",
+        "line 2, column 1: invalid range start '10 ': invalid digit found in string"
+    )]
+    #[case(
+        "3-5\n10-1x\n\n1",
+        "line 2, column 4: invalid range end '1x': invalid digit found in string"
+    )]
+    #[case("3-5\n\n1\n 5", "line 4, column 1: unexpected whitespace in ' 5'")]
+    #[case("3-5\n\n1\n\n5", "line 4, column 1: unexpected blank line in IDs")]
+    #[case(
+        "3-5\n\n1\n5-6",
+        "line 4, column 1: invalid ID '5-6': invalid digit found in string"
+    )]
+    #[case(
+        "3-5\n3\n",
+        "line 2, column 1: expected a range 'start-end' but found ID '3', is the blank line missing?"
+    )]
+    fn test_parse_errors(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(
+            parse_input(input.lines()).map_err(String::from),
+            Err(expected.to_string())
+        );
+        verify!(part1, input, format!("ERROR: {}", expected));
+    }
+
+    #[test]
+    fn membership_matches_linear_scan() {
+        check_oracle!(
+            random_ranges,
+            |(ranges, numbers): &RangesAndNumbers| {
+                let merged = merge_ranges(ranges.clone());
+                let contained = numbers
+                    .iter()
+                    .map(|&num| merged_contains(&merged, num))
+                    .collect_vec();
+                (contained, containing_ranges(ranges, numbers))
+            },
+            |(ranges, numbers): &RangesAndNumbers| {
+                let containing = numbers
+                    .iter()
+                    .map(|num| {
+                        (0..ranges.len())
+                            .filter(|&i| ranges[i].contains(num))
+                            .collect_vec()
+                    })
+                    .collect_vec();
+                let contained = containing.iter().map(|c| !c.is_empty()).collect_vec();
+                (contained, containing)
+            }
+        );
+    }
+
+    #[test]
+    fn merge_ranges_matches_expanded_set() {
+        check_oracle!(
+            random_ranges,
+            |(ranges, numbers): &RangesAndNumbers| {
+                let merged = merge_ranges(ranges.clone());
+                let covered = merged
+                    .iter()
+                    .map(|range| range.clone().count())
+                    .sum::<usize>();
+                let contained = numbers
+                    .iter()
+                    .filter(|num| merged.iter().any(|range| range.contains(num)))
+                    .count();
+                (covered, contained)
+            },
+            |(ranges, numbers): &RangesAndNumbers| {
+                let expanded: HashSet<u64> = ranges.iter().cloned().flatten().collect();
+                let contained = numbers.iter().filter(|num| expanded.contains(num)).count();
+                (expanded.len(), contained)
+            }
+        );
     }
 }
@@ -1,8 +1,12 @@
+use advent_of_code::input::{InputFormat, Layout};
+use advent_of_code::parse::{self, ParseError, Span};
 use advent_of_code::{Named, Runner, create_runner, named};
 use itertools::Itertools;
+use std::iter::{once, repeat_with};
+use std::ops::Range;
 use std::str::{FromStr, Lines};
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 enum Operation {
     Add,
     Multiply,
@@ -47,72 +51,158 @@ impl Problem {
     }
 }
 
-fn parse_problems1(mut input: Lines) -> Vec<Problem> {
-    let problems = input
-        .next_back()
-        .expect("at least one line")
-        .split_whitespace()
-        .map(|s| s.trim().parse::<Operation>().expect("valid operation"))
-        .map(Problem::new)
-        .collect_vec();
-    input.fold(problems, |problems, line| {
-        line.split_whitespace()
-            .map(|s| s.parse::<u64>().expect("valid number"))
-            .zip_eq(problems)
-            .map(|(number, problem)| problem.add_number(number))
-            .collect_vec()
-    })
+struct Block {
+    columns: Range<usize>,
+    operation: Operation,
 }
 
-fn part1(input: Lines) -> String {
-    parse_problems1(input)
-        .into_iter()
-        .map(|problem| problem.solve())
-        .sum::<u64>()
-        .to_string()
+// problems are separated by columns which are blank in every line
+struct Worksheet<'a> {
+    lines: Vec<Span<'a>>,
+    rows: Vec<Vec<char>>,
+    blocks: Vec<Block>,
 }
 
-fn parse_problems2(input: Lines) -> Vec<Problem> {
-    let lines = input.map(|line| line.chars().collect_vec()).collect_vec();
-    let max_len = lines.iter().map(|line| line.len()).max().unwrap_or(0);
-    let num_digits = lines.len() - 1;
-    let mut problems = Vec::new();
-    let mut numbers = Vec::<u64>::new();
-    for column in (0..max_len).rev() {
-        let maybe_number = (0..num_digits)
-            .flat_map(|line| lines[line].get(column))
-            .collect::<String>()
-            .trim()
-            .parse::<u64>()
-            .ok();
-        if let Some(number) = maybe_number {
-            numbers.push(number);
+impl<'a> Worksheet<'a> {
+    fn parse(input: Lines<'a>) -> Result<Self, ParseError> {
+        let mut lines = parse::lines(input).collect_vec();
+        let operators_line = lines
+            .pop()
+            .ok_or_else(|| Span::new("", 1).error("worksheet is empty"))?;
+        let operators = operators_line.text.chars().collect_vec();
+        let mut rows = lines
+            .iter()
+            .map(|line| line.text.chars().collect_vec())
+            .collect_vec();
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        for (line, row) in lines.iter().zip(&mut rows) {
+            if let Some(x) = row.iter().position(|c| *c != ' ' && !c.is_ascii_digit()) {
+                return Err(line.error_at(x, format!("unexpected character '{}'", row[x])));
+            }
+            row.resize(width, ' ');
         }
-        let maybe_operation = lines
-            .last()
-            .unwrap()
-            .get(column)
-            .filter(|c| !c.is_whitespace())
-            .map(|c| c.to_string().parse::<Operation>().expect("valid operation"));
-        if let Some(operation) = maybe_operation {
-            problems.push(Problem { numbers, operation });
-            numbers = Vec::new();
+        let columns = (0..width)
+            .chunk_by(|&x| rows.iter().all(|row| row[x] == ' '))
+            .into_iter()
+            .filter_map(|(blank, mut xs)| {
+                let start = xs.next()?;
+                let end = xs.last().unwrap_or(start) + 1;
+                (!blank).then_some(start..end)
+            })
+            .collect_vec();
+        let mut operations = repeat_with(|| None).take(columns.len()).collect_vec();
+        for (x, c) in operators.iter().enumerate().filter(|(_, c)| **c != ' ') {
+            let operation = c
+                .to_string()
+                .parse::<Operation>()
+                .map_err(|e| operators_line.error_at(x, e))?;
+            match columns.iter().position(|range| range.contains(&x)) {
+                Some(block) if columns[block].start == x => operations[block] = Some(operation),
+                Some(block) => {
+                    return Err(operators_line.error_at(
+                        x,
+                        format!(
+                            "operator should be in column {}, the first column of its block",
+                            columns[block].start + 1
+                        ),
+                    ));
+                }
+                None => {
+                    return Err(
+                        operators_line.error_at(x, "operator does not line up with any block")
+                    );
+                }
+            }
         }
+        let blocks = columns
+            .into_iter()
+            .zip(operations)
+            .map(|(columns, operation)| match operation {
+                Some(operation) => Ok(Block { columns, operation }),
+                None => Err(operators_line.error_at(columns.start, "block has no operator")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            lines,
+            rows,
+            blocks,
+        })
+    }
+
+    // the digits at `cells` must form one contiguous run
+    fn read_number(&self, cells: &[(usize, usize)]) -> Result<u64, ParseError> {
+        let mut digits = cells
+            .iter()
+            .map(|&(y, x)| (y, x, self.rows[y][x]))
+            .skip_while(|(_, _, c)| *c == ' ');
+        let Some((y, x, first)) = digits.next() else {
+            let (y, x) = cells[0];
+            return Err(self.lines[y].error_at(x, "block is misaligned, number is missing"));
+        };
+        let number = once(first)
+            .chain(
+                digits
+                    .by_ref()
+                    .map_while(|(_, _, c)| Some(c).filter(|c| *c != ' ')),
+            )
+            .collect::<String>();
+        if let Some((y, x, _)) = digits.find(|(_, _, c)| *c != ' ') {
+            return Err(self.lines[y].error_at(x, "block is misaligned, number has a gap"));
+        }
+        number.parse().map_err(|e| self.lines[y].error_at(x, e))
+    }
+
+    // each row of a block is one number
+    fn problems_by_rows(&self) -> Result<Vec<Problem>, ParseError> {
+        self.blocks
+            .iter()
+            .map(|block| {
+                (0..self.rows.len())
+                    .map(|y| self.read_number(&block.columns.clone().map(|x| (y, x)).collect_vec()))
+                    .fold_ok(Problem::new(block.operation), Problem::add_number)
+            })
+            .collect()
+    }
+
+    // each column of a block is one number, read right to left
+    fn problems_by_columns(&self) -> Result<Vec<Problem>, ParseError> {
+        self.blocks
+            .iter()
+            .rev()
+            .map(|block| {
+                block
+                    .columns
+                    .clone()
+                    .rev()
+                    .map(|x| self.read_number(&(0..self.rows.len()).map(|y| (y, x)).collect_vec()))
+                    .fold_ok(Problem::new(block.operation), Problem::add_number)
+            })
+            .collect()
     }
-    problems
 }
 
-fn part2(input: Lines) -> String {
-    parse_problems2(input)
+fn solve_all(problems: Vec<Problem>) -> String {
+    problems
         .into_iter()
         .map(|problem| problem.solve())
         .sum::<u64>()
         .to_string()
 }
 
+fn part1(input: Lines) -> Result<String, String> {
+    Ok(solve_all(Worksheet::parse(input)?.problems_by_rows()?))
+}
+
+fn part2(input: Lines) -> Result<String, String> {
+    Ok(solve_all(Worksheet::parse(input)?.problems_by_columns()?))
+}
+
+// blocks are found by column, so lines which have lost their trailing spaces are padded back
+const INPUT_FORMAT: InputFormat = InputFormat::new(Layout::Rectangular { padding: Some(' ') });
+
 fn main() {
-    let input = include_str!("input.txt");
-    let runner: &Runner = create_runner!();
+    let runner: &Runner = create_runner!(INPUT_FORMAT);
+    let input = &runner.load(include_str!("input.txt"));
     runner.run(named!(part1), input);
     runner.run(named!(part2), input);
 }
@@ -120,21 +210,16 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use advent_of_code::verify;
+    use advent_of_code::{examples, input, verify};
 
-    #[test]
-    fn example() {
-        let input = include_str!("example.txt");
-        verify!(part1, input, "4277556");
-        verify!(part2, input, "3263827");
-    }
+    examples!();
 
     #[test]
-    fn test_parse_problems2() {
+    fn test_problems_by_columns() {
         let input = include_str!("example.txt");
         assert_eq!(
-            parse_problems2(input.lines()),
-            vec![
+            Worksheet::parse(input.lines()).and_then(|worksheet| worksheet.problems_by_columns()),
+            Ok(vec![
                 Problem {
                     numbers: vec![4, 431, 623],
                     operation: Operation::Add,
@@ -151,7 +236,66 @@ mod tests {
                     numbers: vec![356, 24, 1],
                     operation: Operation::Multiply,
                 },
-            ]
+            ])
+        );
+    }
+
+    #[test]
+    fn test_mangled_example() {
+        let example = include_str!("example.txt");
+        let mangled = example.lines().map(str::trim_end).collect::<Vec<_>>();
+        let mangled = format!("\u{feff}{}\r\n\r\n", mangled.join("\r\n"));
+        let input = input::normalise(&mangled, INPUT_FORMAT);
+        assert_eq!(input, input::normalise(example, INPUT_FORMAT));
+        verify!(part1, input, "4277556");
+        verify!(part2, input, "3263827");
+    }
+
+    #[test]
+    fn test_stray_character() {
+        assert_eq!(
+            Worksheet::parse("12 3\n4x 5\n*  +".lines())
+                .err()
+                .map(String::from),
+            Some("line 2, column 2: unexpected character 'x'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_misaligned_operator() {
+        assert_eq!(
+            Worksheet::parse("12 3\n45 6\n *  +".lines())
+                .err()
+                .map(String::from),
+            Some(
+                "line 3, column 2: operator should be in column 1, the first column of its block"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            Worksheet::parse("12  3\n45  6\n*  +".lines())
+                .err()
+                .map(String::from),
+            Some("line 3, column 4: operator does not line up with any block".to_string())
+        );
+        assert_eq!(
+            Worksheet::parse("12 3\n45 6\n*".lines())
+                .err()
+                .map(String::from),
+            Some("line 3, column 4: block has no operator".to_string())
+        );
+    }
+
+    #[test]
+    fn test_misaligned_block() {
+        let worksheet = Worksheet::parse("12 3\n   5\n6  7\n*  +".lines()).unwrap();
+        assert_eq!(
+            worksheet.problems_by_rows().err().map(String::from),
+            Some("line 2, column 1: block is misaligned, number is missing".to_string())
+        );
+        assert_eq!(
+            worksheet.problems_by_columns().err().map(String::from),
+            Some("line 3, column 1: block is misaligned, number has a gap".to_string())
         );
     }
 }
@@ -1,15 +1,37 @@
+use advent_of_code::input::{InputFormat, Layout};
 use advent_of_code::{Named, Runner, create_runner, named};
 use core::{fmt, panic};
 use itertools::Itertools;
-use std::iter::repeat_n;
-use std::ops::Range;
+use num::{BigUint, One, Zero};
+use std::collections::{HashMap, HashSet};
 use std::str::Lines;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Direction {
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    fn is_reverse_of(&self, other: Direction) -> bool {
+        matches!(
+            (self, other),
+            (Direction::Left, Direction::Right) | (Direction::Right, Direction::Left)
+        )
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 enum Cell {
     Empty,
     Splitter,
-    Beam,
+    LeftSplitter,
+    RightSplitter,
+    // '/' and '\\', beams can't travel up the manifold so a mirror turning one upwards absorbs it
+    ForwardMirror,
+    BackMirror,
+    Absorber,
 }
 
 impl Cell {
@@ -17,9 +39,41 @@ impl Cell {
         match self {
             Cell::Empty => '.',
             Cell::Splitter => '^',
-            Cell::Beam => '|',
+            Cell::LeftSplitter => '<',
+            Cell::RightSplitter => '>',
+            Cell::ForwardMirror => '/',
+            Cell::BackMirror => '\\',
+            Cell::Absorber => '#',
         }
     }
+
+    fn is_splitter(&self) -> bool {
+        matches!(
+            self,
+            Cell::Splitter | Cell::LeftSplitter | Cell::RightSplitter
+        )
+    }
+
+    // the directions a beam leaves this cell in, a beam never turns back the way it came
+    // so chained splitters only pass it on outwards
+    fn deflect(self, direction: Direction) -> impl Iterator<Item = Direction> {
+        use Direction::*;
+        let outputs: &[Direction] = match (self, direction) {
+            (Cell::Empty, _) => &[Down],
+            (Cell::Splitter, _) => &[Left, Right],
+            (Cell::LeftSplitter, _) => &[Left],
+            (Cell::RightSplitter, _) => &[Right],
+            (Cell::ForwardMirror, Down) => &[Left],
+            (Cell::BackMirror, Down) => &[Right],
+            (Cell::ForwardMirror, Left) | (Cell::BackMirror, Right) => &[Down],
+            (Cell::ForwardMirror, Right) | (Cell::BackMirror, Left) => &[],
+            (Cell::Absorber, _) => &[],
+        };
+        outputs
+            .iter()
+            .copied()
+            .filter(move |output| !output.is_reverse_of(direction))
+    }
 }
 
 struct TachyonManifold {
@@ -27,23 +81,6 @@ struct TachyonManifold {
     start: (usize, usize),
 }
 
-impl fmt::Display for TachyonManifold {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for (y, row) in self.cells.iter().enumerate() {
-            for (x, cell) in row.iter().enumerate() {
-                let cell = if self.start == (x, y) {
-                    'S'
-                } else {
-                    cell.as_char()
-                };
-                write!(f, "{}", cell)?;
-            }
-            writeln!(f)?;
-        }
-        Ok(())
-    }
-}
-
 impl TachyonManifold {
     fn parse(input: Lines) -> Self {
         let mut start = None;
@@ -55,6 +92,11 @@ impl TachyonManifold {
                     .map(|(x, c)| match c {
                         '.' => Cell::Empty,
                         '^' => Cell::Splitter,
+                        '<' => Cell::LeftSplitter,
+                        '>' => Cell::RightSplitter,
+                        '/' => Cell::ForwardMirror,
+                        '\\' => Cell::BackMirror,
+                        '#' => Cell::Absorber,
                         'S' => {
                             start = Some((x, y)); // Placeholder for start position
                             Cell::Empty
@@ -68,134 +110,365 @@ impl TachyonManifold {
         TachyonManifold { cells, start }
     }
 
-    fn start(&mut self) -> Range<usize> {
-        let beam_y = self.start.1 + 1;
-        let beam_cell = &mut self.cells[beam_y][self.start.0];
-        assert_eq!(
-            beam_cell,
-            &Cell::Empty,
-            "Expected Empty cell below the start position"
-        );
-        *beam_cell = Cell::Beam;
-        beam_y..(self.cells.len() - 1)
-    }
-
-    fn step(&mut self, y: usize) -> usize {
-        let current = self.cells[y].clone();
-        let next = &mut (self.cells[y + 1]);
-        let mut beam_splits = 0;
-        for (x, cell) in current.into_iter().enumerate() {
-            if cell == Cell::Beam {
-                let next_cell = &mut next[x];
-                match *next_cell {
-                    Cell::Empty => {
-                        *next_cell = Cell::Beam;
-                    }
-                    Cell::Splitter => {
-                        beam_splits += 1;
-                        for split_x in [x - 1, x + 1].into_iter() {
-                            let split_cell = &mut next[split_x];
-                            match *split_cell {
-                                Cell::Empty => {
-                                    *split_cell = Cell::Beam;
-                                }
-                                Cell::Splitter => {
-                                    panic!(
-                                        "Split beam hit another Splitter at ({}, {})",
-                                        split_x,
-                                        y + 1
-                                    );
-                                }
-                                Cell::Beam => {}
-                            }
-                        }
-                    }
-                    Cell::Beam => {}
-                };
-            }
-        }
-        beam_splits
-    }
-
-    fn run(&mut self) -> usize {
-        self.start().map(|y| self.step(y)).sum::<usize>()
+    // None when the beam leaves the manifold
+    fn next(&self, (x, y): (usize, usize), direction: Direction) -> Option<(usize, usize)> {
+        let next = match direction {
+            Direction::Down => (x, y + 1),
+            Direction::Left => (x.checked_sub(1)?, y),
+            Direction::Right => (x + 1, y),
+        };
+        self.cells
+            .get(next.1)
+            .and_then(|row| row.get(next.0))
+            .map(|_| next)
     }
 
-    fn count_timelines(&self) -> usize {
-        let results_row = repeat_n(None, self.cells[0].len()).collect_vec();
-        let mut results = repeat_n(results_row, self.cells.len()).collect_vec();
-        self.count_timelines_pos((self.start.0, self.start.1 + 1), &mut results)
+    // sends a beam down from the start one row at a time, carrying the number of timelines
+    // leaving each column downwards. Within a row beams only move outwards, so leftward beams are
+    // resolved right to left and rightward ones left to right after those entering from above
+    fn propagate(&self) -> Propagation<'_> {
+        let mut propagation = Propagation {
+            manifold: self,
+            splitters_hit: HashSet::new(),
+            beams: HashSet::new(),
+            timelines: Timelines {
+                bottom: Vec::new(),
+                elsewhere: BigUint::zero(),
+            },
+        };
+        let mut leaving = vec![BigUint::zero(); self.cells[self.start.1].len()];
+        leaving[self.start.0] = BigUint::one();
+        for y in self.start.1 + 1..self.cells.len() {
+            leaving = self.propagate_row(y, leaving, &mut propagation);
+        }
+        propagation.timelines.bottom = leaving;
+        propagation
     }
 
-    fn count_timelines_pos(
+    fn propagate_row(
         &self,
-        pos: (usize, usize),
-        results: &mut Vec<Vec<Option<usize>>>,
-    ) -> usize {
-        if pos.1 >= self.cells.len() {
-            1
-        } else {
-            match results[pos.1][pos.0] {
-                Some(count) => count,
-                None => {
-                    let count = self.count_timelines_pos_internal(pos, results);
-                    results[pos.1][pos.0] = Some(count);
-                    count
+        y: usize,
+        above: Vec<BigUint>,
+        propagation: &mut Propagation,
+    ) -> Vec<BigUint> {
+        let width = self.cells[y].len();
+        let elsewhere = &mut propagation.timelines.elsewhere;
+        let mut arriving = HashMap::new();
+        for (x, count) in above.into_iter().enumerate() {
+            if count.is_zero() {
+                continue;
+            } else if x < width {
+                arriving.insert((x, Direction::Down), count);
+            } else {
+                *elsewhere += count;
+            }
+        }
+        let order = (0..width)
+            .map(|x| (x, Direction::Down))
+            .chain((0..width).rev().map(|x| (x, Direction::Left)))
+            .chain((0..width).map(|x| (x, Direction::Right)));
+        let mut leaving = vec![BigUint::zero(); width];
+        for (x, direction) in order {
+            let Some(count) = arriving.remove(&(x, direction)) else {
+                continue;
+            };
+            let cell = self.cells[y][x];
+            if cell == Cell::Empty {
+                propagation.beams.insert((x, y));
+            } else if cell.is_splitter() {
+                propagation.splitters_hit.insert((x, y));
+            }
+            let mut outputs = cell.deflect(direction).peekable();
+            if outputs.peek().is_none() {
+                *elsewhere += &count;
+            }
+            for output in outputs {
+                match (output, self.next((x, y), output)) {
+                    (Direction::Down, _) => leaving[x] += &count,
+                    (_, Some((x, _))) => {
+                        *arriving.entry((x, output)).or_insert_with(BigUint::zero) += &count
+                    }
+                    (_, None) => *elsewhere += &count,
                 }
             }
         }
+        leaving
     }
+}
 
-    fn count_timelines_pos_internal(
-        &self,
-        pos: (usize, usize),
-        results: &mut Vec<Vec<Option<usize>>>,
-    ) -> usize {
-        match self.cells[pos.1][pos.0] {
-            Cell::Empty => self.count_timelines_pos((pos.0, pos.1 + 1), results),
-            Cell::Splitter => {
-                self.count_timelines_pos((pos.0 - 1, pos.1), results)
-                    + self.count_timelines_pos((pos.0 + 1, pos.1), results)
+// everything found by one pass of the beam through the manifold
+struct Propagation<'a> {
+    manifold: &'a TachyonManifold,
+    splitters_hit: HashSet<(usize, usize)>,
+    // the empty cells a beam passes through
+    beams: HashSet<(usize, usize)>,
+    timelines: Timelines,
+}
+
+impl fmt::Display for Propagation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.manifold.cells.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                let cell = if self.manifold.start == (x, y) {
+                    'S'
+                } else if self.beams.contains(&(x, y)) {
+                    '|'
+                } else {
+                    cell.as_char()
+                };
+                write!(f, "{}", cell)?;
             }
-            Cell::Beam => panic!("Beams should not be present"),
+            writeln!(f)?;
         }
+        Ok(())
+    }
+}
+
+// every path a beam can take is one timeline, whether it leaves the manifold or is absorbed
+#[derive(Debug, PartialEq)]
+struct Timelines {
+    // timelines leaving through the bottom row, by column
+    bottom: Vec<BigUint>,
+    // timelines leaving through the sides or absorbed
+    elsewhere: BigUint,
+}
+
+impl Timelines {
+    fn total(&self) -> BigUint {
+        self.bottom.iter().sum::<BigUint>() + &self.elsewhere
     }
 }
 
 fn part1(input: Lines) -> String {
-    let mut tachyon_manifold = TachyonManifold::parse(input);
-    tachyon_manifold.run().to_string()
+    let tachyon_manifold = TachyonManifold::parse(input);
+    tachyon_manifold.propagate().splitters_hit.len().to_string()
 }
 
 fn part2(input: Lines) -> String {
-    TachyonManifold::parse(input).count_timelines().to_string()
+    let tachyon_manifold = TachyonManifold::parse(input);
+    tachyon_manifold.propagate().timelines.total().to_string()
+}
+
+fn beams(input: Lines) -> String {
+    TachyonManifold::parse(input).propagate().to_string()
 }
 
+fn distribution(input: Lines) -> String {
+    let tachyon_manifold = TachyonManifold::parse(input);
+    let timelines = tachyon_manifold.propagate().timelines;
+    timelines
+        .bottom
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| !count.is_zero())
+        .map(|(x, count)| format!("column {}: {}", x, count))
+        .chain([format!("elsewhere: {}", timelines.elsewhere)])
+        .join("\n")
+}
+
+// beams falling past the end of a short row are counted as elsewhere, so a ragged manifold
+// is reported rather than padded out
+const INPUT_FORMAT: InputFormat = InputFormat::new(Layout::Rectangular { padding: None });
+
 fn main() {
-    let input = include_str!("input.txt");
-    let runner: &Runner = create_runner!();
+    let runner: &Runner = create_runner!(INPUT_FORMAT);
+    let input = &runner.load(include_str!("input.txt"));
     runner.run(named!(part1), input);
     runner.run(named!(part2), input);
+    runner.run_if_requested(named!(beams), input);
+    runner.run_if_requested(named!(distribution), input);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use advent_of_code::verify;
+    use advent_of_code::{check_oracle, examples, verify};
+    use rand::Rng;
+    use rstest::rstest;
+    use std::collections::BTreeSet;
+    use std::iter::once;
+
+    examples!();
+
+    #[rstest]
+    #[case::chained_splitters(".S...\n.^^^.\n.....", 3, 2)]
+    #[case::left_edge("S..\n^..\n...", 1, 2)]
+    #[case::right_edge("..S\n..^\n...", 1, 2)]
+    #[case::absorber("S\n#\n.", 0, 1)]
+    #[case::left_splitter(".S.\n...\n.<.\n...", 1, 1)]
+    #[case::right_splitter(".S.\n.>.\n...", 1, 1)]
+    #[case::left_splitter_from_behind("S..\n^<.\n...", 2, 2)]
+    #[case::forward_mirror("..S\n.^/\n...", 1, 1)]
+    #[case::back_mirror("S..\n\\^.\n...", 1, 1)]
+    #[case::mirror_turns_down("..S\n./^\n...", 1, 2)]
+    #[case::mirror_turns_up("S..\n^/.\n...", 1, 2)]
+    #[case::back_mirror_turns_down("S...\n^\\^.\n....", 1, 2)]
+    #[case::start_on_bottom_row("...\n.S.", 0, 1)]
+    fn test_cell_kinds(
+        #[case] input: &str,
+        #[case] expected_splits: usize,
+        #[case] expected_timelines: usize,
+    ) {
+        verify!(part1, input, expected_splits.to_string());
+        verify!(part2, input, expected_timelines.to_string());
+    }
+
+    #[test]
+    fn test_chained_splitters_layout() {
+        let tachyon_manifold = TachyonManifold::parse("..S..\n.....\n..^^.\n.....".lines());
+        let propagation = tachyon_manifold.propagate();
+        assert_eq!(propagation.splitters_hit.len(), 2);
+        assert_eq!(format!("{}", propagation), "..S..\n..|..\n.|^^|\n.|..|\n");
+        assert_eq!(
+            propagation.timelines,
+            Timelines {
+                bottom: [0u8, 1, 0, 0, 1].map(BigUint::from).to_vec(),
+                elsewhere: BigUint::zero(),
+            }
+        );
+    }
+
+    // splitters in a pyramid, so every row of splitters doubles the timelines
+    fn splitter_pyramid(levels: usize) -> String {
+        let width = 2 * levels + 1;
+        let mut rows = vec![format!("{:^width$}", "S").replace(' ', ".")];
+        for level in 0..levels {
+            rows.push(".".repeat(width));
+            let row = (0..width)
+                .map(|x| {
+                    let offset = x.abs_diff(levels);
+                    if offset <= level && (level - offset) % 2 == 0 {
+                        '^'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>();
+            rows.push(row);
+        }
+        rows.push(".".repeat(width));
+        rows.join("\n")
+    }
 
     #[test]
-    fn example() {
-        let input = include_str!("example.txt");
-        verify!(part1, input, "21");
-        verify!(part2, input, "40");
+    fn test_timelines_beyond_u64() {
+        let input = splitter_pyramid(70);
+        verify!(part2, input, BigUint::from(2u8).pow(70).to_string());
     }
 
     #[test]
-    fn test_manifold_layout() {
-        let input = include_str!("example.txt");
-        let expected = include_str!("expected_part1.txt");
-        let mut tachyon_manifold = TachyonManifold::parse(input.lines());
-        tachyon_manifold.run();
-        assert_eq!(format!("{}", tachyon_manifold), expected);
+    fn test_tall_manifold() {
+        let input = format!("S\n{}", ".\n".repeat(100_000));
+        verify!(part2, input, "1");
+    }
+
+    // the rows below the start, each as wide as the start row
+    #[derive(Debug)]
+    struct RandomManifold {
+        start: usize,
+        width: usize,
+        rows: Vec<String>,
+    }
+
+    impl RandomManifold {
+        // about half of the cells are empty, the rest are any of the other kinds of cell
+        fn generate(rng: &mut impl Rng) -> Self {
+            let width = rng.random_range(1..=6);
+            let cells = ['^', '<', '>', '/', '\\', '#'];
+            let rows = (0..rng.random_range(0..8))
+                .map(|_| {
+                    (0..width)
+                        .map(|_| {
+                            if rng.random_bool(0.5) {
+                                '.'
+                            } else {
+                                cells[rng.random_range(0..cells.len())]
+                            }
+                        })
+                        .collect()
+                })
+                .collect();
+            Self {
+                start: rng.random_range(0..width),
+                width,
+                rows,
+            }
+        }
+
+        fn render(&self) -> String {
+            let start = format!(
+                "{}S{}",
+                ".".repeat(self.start),
+                ".".repeat(self.width - 1 - self.start)
+            );
+            once(start).chain(self.rows.iter().cloned()).join("\n")
+        }
+    }
+
+    // splitters hit, beams and timelines
+    type Summary = (
+        BTreeSet<(usize, usize)>,
+        BTreeSet<(usize, usize)>,
+        Timelines,
+    );
+
+    fn summarise(propagation: Propagation) -> Summary {
+        (
+            propagation.splitters_hit.into_iter().collect(),
+            propagation.beams.into_iter().collect(),
+            propagation.timelines,
+        )
+    }
+
+    // follows every path one at a time
+    fn enumerate_paths(
+        manifold: &TachyonManifold,
+        pos: (usize, usize),
+        direction: Direction,
+        summary: &mut Summary,
+    ) {
+        let cell = manifold.cells[pos.1][pos.0];
+        if cell == Cell::Empty {
+            summary.1.insert(pos);
+        } else if cell.is_splitter() {
+            summary.0.insert(pos);
+        }
+        let mut outputs = cell.deflect(direction).peekable();
+        if outputs.peek().is_none() {
+            summary.2.elsewhere += 1u8;
+        }
+        for output in outputs {
+            match manifold.next(pos, output) {
+                Some(next) => enumerate_paths(manifold, next, output, summary),
+                None if output == Direction::Down => summary.2.bottom[pos.0] += 1u8,
+                None => summary.2.elsewhere += 1u8,
+            }
+        }
+    }
+
+    #[test]
+    fn propagation_matches_path_enumeration() {
+        check_oracle!(
+            RandomManifold::generate,
+            |random: &RandomManifold| {
+                let input = random.render();
+                summarise(TachyonManifold::parse(input.lines()).propagate())
+            },
+            |random: &RandomManifold| {
+                let input = random.render();
+                let manifold = TachyonManifold::parse(input.lines());
+                let width = manifold.cells.last().unwrap().len();
+                let timelines = Timelines {
+                    bottom: vec![BigUint::zero(); width],
+                    elsewhere: BigUint::zero(),
+                };
+                let mut summary = (BTreeSet::new(), BTreeSet::new(), timelines);
+                match manifold.next(manifold.start, Direction::Down) {
+                    Some(pos) => enumerate_paths(&manifold, pos, Direction::Down, &mut summary),
+                    None => summary.2.bottom[manifold.start.0] += 1u8,
+                }
+                summary
+            }
+        );
     }
 }
@@ -1,35 +1,26 @@
+use advent_of_code::vector::Vector;
 use advent_of_code::{Named, Runner, create_runner, named};
+use glam::IVec3;
 use itertools::{Itertools, repeat_n};
 use std::str::{FromStr, Lines};
 
 #[derive(Debug)]
 struct JunctionBox {
-    pos: [i32; 3],
+    pos: IVec3,
 }
 
 impl FromStr for JunctionBox {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let pos = s
-            .splitn(3, ',')
-            .flat_map(|n| n.parse::<i32>())
-            .collect_array()
-            .ok_or(format!("unable to parse '{}'", s))?;
+        let pos = IVec3::parse(s)?;
         Ok(Self { pos })
     }
 }
 
 impl JunctionBox {
-    fn distance_squared_to(&self, other: &Self) -> u64 {
-        self.pos
-            .iter()
-            .zip(other.pos)
-            .map(|(&a, b)| {
-                let diff = (a - b).unsigned_abs() as u64;
-                diff * diff
-            })
-            .sum()
+    fn distance_squared_to(&self, other: &Self) -> u128 {
+        self.pos.euclidean_squared(other.pos)
     }
 }
 
@@ -41,7 +32,7 @@ fn parse_input(input: Lines) -> Vec<JunctionBox> {
 
 #[derive(Eq, PartialEq, PartialOrd, Ord)]
 struct Connection {
-    distance_squared: u64,
+    distance_squared: u128,
     boxes: [usize; 2],
 }
 
@@ -138,7 +129,7 @@ fn part2(input: Lines) -> String {
         if components.largest_components(1)[0] == boxes.len() {
             return connection
                 .boxes
-                .map(|b| boxes[b].pos[0] as u64)
+                .map(|b| boxes[b].pos.x as u64)
                 .iter()
                 .product::<u64>()
                 .to_string();
@@ -148,8 +139,8 @@ fn part2(input: Lines) -> String {
 }
 
 fn main() {
-    let input = include_str!("input.txt");
     let runner: &Runner = create_runner!();
+    let input = &runner.load(include_str!("input.txt"));
     runner.run(named!(part1), input);
     runner.run(named!(part2), input);
 }
@@ -159,17 +150,12 @@ mod tests {
     use std::collections::HashMap;
 
     use super::*;
-    use advent_of_code::verify;
+    use advent_of_code::examples;
 
-    #[test]
-    fn example() {
-        let input = include_str!("example.txt");
-        verify!(part1, input, "40");
-        verify!(part2, input, "25272");
-    }
+    examples!();
 
     fn readable_connection(connection: &Connection, boxes: &[JunctionBox]) -> [[i32; 3]; 2] {
-        let mut connection = connection.boxes.map(|b| boxes[b].pos);
+        let mut connection = connection.boxes.map(|b| boxes[b].pos.to_array());
         connection.sort();
         connection
     }
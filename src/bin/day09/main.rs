@@ -1,153 +1,200 @@
+use advent_of_code::parse::{self, ParseError, Span};
+use advent_of_code::vector::{Coords, Vector, bounding_box};
 use advent_of_code::{Named, Runner, create_runner, named};
+use glam::I64Vec2;
 use itertools::Itertools;
+use std::cmp::Reverse;
 use std::ops::RangeInclusive;
+use std::{fmt, fs};
 use std::{
-    iter::repeat_n,
-    ops::{Add, Sub},
-    str::{FromStr, Lines},
+    iter::{once, repeat_n},
+    str::Lines,
 };
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-struct Pos {
-    x: i64,
-    y: i64,
-}
+type Pos = I64Vec2;
 
-impl Pos {
-    fn rectangle_area_with(&self, other: &Pos) -> i64 {
-        let size = (*other - *self).abs() + 1;
-        size.x * size.y
-    }
+fn rectangle_area(a: &Pos, b: &Pos) -> i64 {
+    ((*a - *b).abs() + 1).element_product()
+}
 
-    fn abs(&self) -> Pos {
-        Self {
-            x: self.x.abs(),
-            y: self.y.abs(),
-        }
-    }
+fn parse_points(input: Lines) -> Result<Vec<Pos>, ParseError> {
+    parse::lines(input)
+        .map(|line| line.parse_with(Pos::parse))
+        .collect()
 }
 
-impl Add for Pos {
-    type Output = Pos;
+#[derive(Debug, PartialEq)]
+struct Rectangle {
+    corners: (Pos, Pos),
+    area: i64,
+}
 
-    fn add(self, rhs: Self) -> Self::Output {
-        Self::Output {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
-        }
+impl fmt::Display for Rectangle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (a, b) = self.corners;
+        write!(f, "{} to {} = {}", Coords(a), Coords(b), self.area)
     }
 }
 
-impl Add<i64> for Pos {
-    type Output = Pos;
+// candidates are checked largest first, so this stops at the k-th valid rectangle
+fn largest_rectangles(
+    points: &[Pos],
+    k: usize,
+    is_valid: impl Fn(&Pos, &Pos) -> bool,
+) -> Vec<Rectangle> {
+    points
+        .iter()
+        .tuple_combinations()
+        .map(|(a, b)| Rectangle {
+            corners: (*a, *b),
+            area: rectangle_area(a, b),
+        })
+        .sorted_by_key(|rectangle| Reverse(rectangle.area))
+        .filter(
+            |Rectangle {
+                 corners: (a, b), ..
+             }| is_valid(a, b),
+        )
+        .take(k)
+        .collect()
+}
 
-    fn add(self, rhs: i64) -> Self::Output {
-        Self::Output {
-            x: self.x + rhs,
-            y: self.y + rhs,
-        }
+fn part1(input: Lines) -> Result<String, ParseError> {
+    let points = parse_points(input)?;
+    let largest = largest_rectangles(&points, 1, |_, _| true);
+    match largest.first() {
+        Some(rectangle) => Ok(rectangle.area.to_string()),
+        None => Err(Span::new("", points.len() + 1).error("expected at least 2 red tiles")),
     }
 }
 
-impl Sub for Pos {
-    type Output = Pos;
+#[derive(Debug, PartialEq)]
+enum Winding {
+    // as seen on screen, with y increasing downwards
+    Clockwise,
+    Anticlockwise,
+}
 
-    fn sub(self, rhs: Self) -> Self::Output {
-        Self::Output {
-            x: self.x - rhs.x,
-            y: self.y - rhs.y,
+// the red tiles must form a simple polygon whose edges are all horizontal or vertical
+fn validate_polygon(points: &[Pos]) -> Result<Winding, String> {
+    if points.len() < 4 {
+        return Err(format!(
+            "polygon needs at least 4 points but has {}",
+            points.len()
+        ));
+    }
+    let edges = (0..points.len())
+        .map(|i| (i, (i + 1) % points.len()))
+        .collect_vec();
+    for &(i, j) in &edges {
+        let (a, b) = (points[i], points[j]);
+        if a.x != b.x && a.y != b.y {
+            return Err(format!(
+                "points {} ({}) and {} ({}) are not on a horizontal or vertical line",
+                i,
+                Coords(a),
+                j,
+                Coords(b)
+            ));
         }
     }
-}
-
-impl Sub<i64> for Pos {
-    type Output = Pos;
-
-    fn sub(self, rhs: i64) -> Self::Output {
-        Self::Output {
-            x: self.x - rhs,
-            y: self.y - rhs,
+    for (i, j) in (0..points.len()).tuple_combinations() {
+        if points[i] == points[j] {
+            return Err(format!(
+                "points {} and {} are both at {}",
+                i,
+                j,
+                Coords(points[i])
+            ));
         }
     }
-}
-
-impl FromStr for Pos {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (x, y) = s
-            .split_once(',')
-            .ok_or(format!("Pos didn't contain at least one ',' \"{}\"", s))?;
-        Ok(Pos {
-            x: x.parse()
-                .map_err(|e| format!("Unable to parse x coord \"{}\" due to: {}", x, e))?,
-            y: y.parse()
-                .map_err(|e| format!("Unable to parse y coord \"{}\" due to: {}", x, e))?,
-        })
+    // axis aligned edges are their own bounding boxes, so they intersect if those overlap, and
+    // edges next to each other may only share their common point
+    for (e, f) in edges.iter().tuple_combinations() {
+        let overlap = |axis: fn(&Pos) -> i64| {
+            let (e0, e1) = (axis(&points[e.0]), axis(&points[e.1]));
+            let (f0, f1) = (axis(&points[f.0]), axis(&points[f.1]));
+            e0.min(e1).max(f0.min(f1))..=e0.max(e1).min(f0.max(f1))
+        };
+        let (xs, ys) = (overlap(|p| p.x), overlap(|p| p.y));
+        let intersect = !xs.is_empty() && !ys.is_empty();
+        let adjacent = e.1 == f.0 || f.1 == e.0;
+        let single_point = xs.start() == xs.end() && ys.start() == ys.end();
+        if intersect && !(adjacent && single_point) {
+            return Err(format!(
+                "edges {}-{} and {}-{} intersect",
+                e.0, e.1, f.0, f.1
+            ));
+        }
     }
-}
-
-fn part1(input: Lines) -> String {
-    let points = input
-        .into_iter()
-        .map(|line| line.parse::<Pos>().unwrap())
-        .collect_vec();
-    points
+    let twice_area = edges
         .iter()
-        .tuple_combinations()
-        .map(|(a, b)| a.rectangle_area_with(b))
-        .max()
-        .expect("at least one element")
-        .to_string()
-}
-
-fn get_bounds(points: &[Pos]) -> (Pos, Pos) {
-    let bounds_x = points.iter().map(|p| p.x).minmax().into_option().unwrap();
-    let bounds_y = points.iter().map(|p| p.y).minmax().into_option().unwrap();
-    (
-        Pos {
-            x: bounds_x.0,
-            y: bounds_y.0,
-        },
-        Pos {
-            x: bounds_x.1,
-            y: bounds_y.1,
-        },
-    )
+        .map(|&(i, j)| points[i].x * points[j].y - points[j].x * points[i].y)
+        .sum::<i64>();
+    Ok(if twice_area > 0 {
+        Winding::Clockwise
+    } else {
+        Winding::Anticlockwise
+    })
 }
 
 #[derive(Clone, Debug)]
 struct Edge {
     x: i64,
-    dy: i64,
+    ys: RangeInclusive<i64>,
 }
 
 impl Edge {
-    fn from_points(a: &Pos, b: &Pos) -> Option<(RangeInclusive<i64>, Self)> {
+    fn from_points(a: &Pos, b: &Pos) -> Option<Self> {
         if a.x == b.x {
-            Some((
-                a.y.min(b.y)..=a.y.max(b.y),
-                Self {
-                    x: a.x,
-                    dy: (b.y - a.y).signum(),
-                },
-            ))
+            Some(Self {
+                x: a.x,
+                ys: a.y.min(b.y)..=a.y.max(b.y),
+            })
         } else {
             None
         }
     }
 }
 
+// the distinct values along one axis, with each gap between them as a single slot since nothing
+// changes within a gap
+struct CompressedAxis {
+    slots: Vec<RangeInclusive<i64>>,
+}
+
+impl CompressedAxis {
+    fn new(values: impl Iterator<Item = i64>) -> Self {
+        let values = values.sorted().dedup().collect_vec();
+        let mut slots = Vec::with_capacity(values.len() * 2);
+        for (i, &value) in values.iter().enumerate() {
+            slots.push(value..=value);
+            if let Some(&next) = values.get(i + 1)
+                && next > value + 1
+            {
+                slots.push(value + 1..=next - 1);
+            }
+        }
+        Self { slots }
+    }
+
+    fn slot(&self, value: i64) -> usize {
+        self.slots.partition_point(|slot| *slot.end() < value)
+    }
+}
+
 struct Rasterizer {
-    min_y: i64,
+    xs: CompressedAxis,
+    ys: CompressedAxis,
     rows: Vec<Vec<Edge>>,
 }
 
 impl Rasterizer {
-    fn new(min_y: i64, max_y: i64) -> Self {
-        let size = (max_y - min_y + 1) as usize;
-        let rows = repeat_n(Vec::new(), size).collect_vec();
-        Self { min_y, rows }
+    fn new(points: &[Pos]) -> Self {
+        let xs = CompressedAxis::new(points.iter().map(|p| p.x));
+        let ys = CompressedAxis::new(points.iter().map(|p| p.y));
+        let rows = repeat_n(Vec::new(), ys.slots.len()).collect_vec();
+        Self { xs, ys, rows }
     }
 
     fn add_polygon(&mut self, points: &[Pos]) {
@@ -160,28 +207,42 @@ impl Rasterizer {
     }
 
     fn add_edge(&mut self, a: &Pos, b: &Pos) {
-        if let Some((range, edge)) = Edge::from_points(a, b) {
-            for y in range {
-                self.rows[(y - self.min_y) as usize].push(edge.clone());
+        if let Some(edge) = Edge::from_points(a, b) {
+            for slot in self.ys.slot(*edge.ys.start())..=self.ys.slot(*edge.ys.end()) {
+                self.rows[slot].push(edge.clone());
             }
         }
     }
 
-    fn print(&self) {
-        for (y, row) in self.rows.iter().enumerate() {
-            println!("rasterizer[{}] = {:?}", y as i64 + self.min_y, row);
-        }
+    fn dump(&self) -> String {
+        self.ys
+            .slots
+            .iter()
+            .zip(&self.rows)
+            .map(|(ys, row)| format!("rasterizer[{:?}] = {:?}", ys, row))
+            .join("\n")
     }
 
     fn render(self) -> Rendered {
         let rows = self
             .rows
             .into_iter()
-            .map(RenderedRow::from_edges)
+            .zip(&self.ys.slots)
+            .map(|(edges, ys)| RenderedRow::from_edges(edges, ys))
             .collect_vec();
+        let mut outside = vec![vec![0; self.xs.slots.len() + 1]; self.ys.slots.len() + 1];
+        for (y, row) in rows.iter().enumerate() {
+            for (x, xs) in self.xs.slots.iter().enumerate() {
+                let is_outside = u32::from(!row.contains(xs));
+                outside[y + 1][x + 1] =
+                    outside[y][x + 1] + outside[y + 1][x] - outside[y][x] + is_outside;
+            }
+        }
         Rendered {
-            min_y: self.min_y,
+            xs: self.xs,
+            ys: self.ys,
             rows,
+            outside,
         }
     }
 }
@@ -192,26 +253,33 @@ struct RenderedRow {
 }
 
 impl RenderedRow {
-    fn from_edges(mut edges: Vec<Edge>) -> Self {
-        edges.sort_by_key(|e| e.x);
-        let mut simplified = Vec::new();
-        if let Some(first) = edges.first() {
-            simplified.push(first.x);
-        }
-        let mut is_inside = true;
-        for (prev, cur) in edges.into_iter().tuple_windows() {
-            if prev.dy != cur.dy {
-                simplified.push(cur.x);
-                is_inside = !is_inside;
-            } else if !is_inside {
-                *simplified.last_mut().unwrap() = cur.x
+    // the tiles in the rows ys are those inside the polygon just above or just below them, found
+    // by pairing up the vertical edges crossing each side
+    fn from_edges(edges: Vec<Edge>, ys: &RangeInclusive<i64>) -> Self {
+        let crossing = |crosses: fn(&Edge, &RangeInclusive<i64>) -> bool| {
+            edges
+                .iter()
+                .filter(|edge| crosses(edge, ys))
+                .map(|edge| edge.x)
+                .sorted()
+                .tuples()
+                .map(|(a, b)| a..=b)
+        };
+        let above = crossing(|edge, ys| edge.ys.start() < ys.start());
+        let below = crossing(|edge, ys| edge.ys.end() > ys.end());
+        let mut intervals = Vec::<RangeInclusive<i64>>::new();
+        for interval in above
+            .chain(below)
+            .sorted_by_key(|interval| *interval.start())
+        {
+            if let Some(last) = intervals.last_mut()
+                && *interval.start() <= last.end() + 1
+            {
+                *last = *last.start()..=*last.end().max(interval.end());
+            } else {
+                intervals.push(interval);
             }
         }
-        let intervals = simplified
-            .into_iter()
-            .tuples()
-            .map(|(a, b)| a..=b)
-            .collect_vec();
         Self { intervals }
     }
 
@@ -222,73 +290,514 @@ impl RenderedRow {
     }
 }
 
+// the polygon's rows over the compressed grid
 struct Rendered {
-    min_y: i64,
+    xs: CompressedAxis,
+    ys: CompressedAxis,
     rows: Vec<RenderedRow>,
+    // outside[y][x] is the number of slots outside the polygon in the slots before y and x
+    outside: Vec<Vec<u32>>,
 }
 
 impl Rendered {
     fn is_rect_valid(&self, a: &Pos, b: &Pos) -> bool {
-        let min_y = (a.y.min(b.y) - self.min_y) as usize;
-        let max_y = (a.y.max(b.y) - self.min_y) as usize;
-        let range_x = a.x.min(b.x)..=a.x.max(b.x);
-        (min_y..=max_y).all(|y| self.rows[y].contains(&range_x))
+        let (x0, x1) = (self.xs.slot(a.x.min(b.x)), self.xs.slot(a.x.max(b.x)) + 1);
+        let (y0, y1) = (self.ys.slot(a.y.min(b.y)), self.ys.slot(a.y.max(b.y)) + 1);
+        let outside = &self.outside;
+        (outside[y1][x1] - outside[y0][x1]) - (outside[y1][x0] - outside[y0][x0]) == 0
     }
 
-    fn print(&self) {
-        for (y, row) in self.rows.iter().enumerate() {
-            println!("rendered[{}] = {:?}", y as i64 + self.min_y, row);
+    fn dump(&self) -> String {
+        self.ys
+            .slots
+            .iter()
+            .zip(&self.rows)
+            .map(|(ys, row)| format!("rendered[{:?}] = {:?}", ys, row))
+            .join("\n")
+    }
+}
+
+fn render_polygon(points: &[Pos]) -> Rendered {
+    let mut rasterizer = Rasterizer::new(points);
+    rasterizer.add_polygon(points);
+    rasterizer.render()
+}
+
+fn part2(input: Lines) -> Result<String, String> {
+    let points = parse_points(input)?;
+    validate_polygon(&points)?;
+    let rendered = render_polygon(&points);
+    Ok(
+        largest_rectangles(&points, 1, |a, b| rendered.is_rect_valid(a, b))
+            .first()
+            .expect("at least one element")
+            .area
+            .to_string(),
+    )
+}
+
+fn validate(input: Lines) -> Result<String, String> {
+    let points = parse_points(input)?;
+    let winding = validate_polygon(&points)?;
+    Ok(format!(
+        "valid polygon of {} points, winding {:?}",
+        points.len(),
+        winding
+    ))
+}
+
+const TOP_K: usize = 5;
+
+// the largest few rectangles for each part, for comparing the near misses with the answer
+fn top_rectangles(input: Lines) -> Result<String, String> {
+    let points = parse_points(input)?;
+    validate_polygon(&points)?;
+    let rendered = render_polygon(&points);
+    let part1 = largest_rectangles(&points, TOP_K, |_, _| true);
+    let part2 = largest_rectangles(&points, TOP_K, |a, b| rendered.is_rect_valid(a, b));
+    Ok([("part1", part1), ("part2", part2)]
+        .into_iter()
+        .flat_map(|(part, rectangles)| {
+            once(format!("{}:", part)).chain(
+                rectangles
+                    .into_iter()
+                    .map(|rectangle| format!("  {}", rectangle)),
+            )
+        })
+        .join("\n"))
+}
+
+// each tile is a unit square with its top left corner at its position
+fn svg_rect(a: &Pos, b: &Pos, style: &str) -> String {
+    format!(
+        r#"<rect x="{}" y="{}" width="{}" height="{}" {}/>"#,
+        a.x.min(b.x),
+        a.y.min(b.y),
+        (a.x - b.x).abs() + 1,
+        (a.y - b.y).abs() + 1,
+        style
+    )
+}
+
+// the interior is shaded green, with the red tiles joined through their centres and the best
+// rectangles outlined on top. points must be a valid polygon
+fn encode_svg(points: &[Pos], rendered: &Rendered, best: &[(&str, Rectangle)]) -> String {
+    let (min, max) = bounding_box(points.iter().copied()).expect("a valid polygon has points");
+    let size = max - min + 3;
+    let mut svg = vec![format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+        min.x - 1,
+        min.y - 1,
+        size.x,
+        size.y
+    )];
+    for (ys, row) in rendered.ys.slots.iter().zip(&rendered.rows) {
+        for interval in &row.intervals {
+            svg.push(svg_rect(
+                &Pos {
+                    x: *interval.start(),
+                    y: *ys.start(),
+                },
+                &Pos {
+                    x: *interval.end(),
+                    y: *ys.end(),
+                },
+                r#"fill="green""#,
+            ));
         }
     }
+    let polygon = points
+        .iter()
+        .map(|p| format!("{},{}", p.x as f64 + 0.5, p.y as f64 + 0.5))
+        .join(" ");
+    svg.push(format!(
+        r#"<polygon points="{}" fill="none" stroke="red" vector-effect="non-scaling-stroke"/>"#,
+        polygon
+    ));
+    for (
+        colour,
+        Rectangle {
+            corners: (a, b), ..
+        },
+    ) in best
+    {
+        let style = format!(
+            r#"fill="none" stroke="{}" stroke-width="3" vector-effect="non-scaling-stroke""#,
+            colour
+        );
+        svg.push(svg_rect(a, b, &style));
+    }
+    svg.push("</svg>".to_string());
+    svg.join("\n")
 }
 
-fn part2(input: Lines) -> String {
-    let points = input
+// writes to $SVG_PATH, or target/day09.svg by default, with part1's rectangle in blue and
+// part2's in orange
+fn svg(input: Lines) -> Result<String, String> {
+    let path = std::env::var("SVG_PATH").unwrap_or_else(|_| "target/day09.svg".to_string());
+    let points = parse_points(input)?;
+    validate_polygon(&points)?;
+    let rendered = render_polygon(&points);
+    let best = [
+        largest_rectangles(&points, 1, |_, _| true),
+        largest_rectangles(&points, 1, |a, b| rendered.is_rect_valid(a, b)),
+    ];
+    let best = ["blue", "orange"]
         .into_iter()
-        .map(|line| line.parse::<Pos>().unwrap())
+        .zip(best.into_iter().flatten())
         .collect_vec();
-    let (min, max) = get_bounds(&points);
+    fs::write(&path, encode_svg(&points, &rendered, &best))
+        .map(|()| format!("wrote {}", path))
+        .map_err(|e| format!("failed to write {}: {}", path, e))
+}
 
-    let mut rasterizer = Rasterizer::new(min.y, max.y);
+fn debug(input: Lines) -> Result<String, String> {
+    let points = parse_points(input)?;
+    validate_polygon(&points)?;
+    let mut rasterizer = Rasterizer::new(&points);
     rasterizer.add_polygon(&points);
-    if points.len() < 100 {
-        rasterizer.print();
-    }
-    let rendered = rasterizer.render();
-    if points.len() < 100 {
-        rendered.print();
-    }
-    points
-        .iter()
-        .tuple_combinations()
-        .filter_map(|(a, b)| {
-            if rendered.is_rect_valid(a, b) {
-                Some(a.rectangle_area_with(b))
-            } else {
-                None
-            }
-        })
-        .max()
-        .expect("at least one element")
-        .to_string()
+    let dump = rasterizer.dump();
+    Ok([dump, rasterizer.render().dump()].join("\n"))
 }
 
 fn main() {
-    let input = include_str!("input.txt");
     let runner: &Runner = create_runner!();
+    let input = &runner.load(include_str!("input.txt"));
     runner.run(named!(part1), input);
     runner.run(named!(part2), input);
+    runner.run_if_requested(named!(validate), input);
+    runner.run_if_requested(named!(top_rectangles), input);
+    runner.run_if_requested(named!(svg), input);
+    runner.run_if_requested(named!(debug), input);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use advent_of_code::verify;
+    use advent_of_code::{check_oracle, examples, verify};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use rstest::rstest;
+
+    examples!();
 
+    // the bottom of the notch is on the boundary, so the row across it is inside all the way
     #[test]
-    fn example() {
+    fn test_notch_bottom_row() {
+        let input = "0,0\n1,0\n1,1\n9,1\n9,0\n10,0\n10,5\n0,5";
+        verify!(part2, input, "50");
+    }
+
+    #[test]
+    fn example_top_rectangles() {
         let input = include_str!("example.txt");
-        verify!(part1, input, "50");
-        verify!(part2, input, "24");
+        assert_eq!(
+            top_rectangles(input.lines()),
+            Ok([
+                "part1:",
+                "  11,1 to 2,5 = 50",
+                "  11,7 to 2,3 = 50",
+                "  9,7 to 2,3 = 40",
+                "  7,1 to 11,7 = 35",
+                "  7,1 to 2,5 = 30",
+                "part2:",
+                "  9,5 to 2,3 = 24",
+                "  11,1 to 9,7 = 21",
+                "  2,5 to 7,3 = 18",
+                "  7,1 to 9,5 = 15",
+                "  11,1 to 9,5 = 15",
+            ]
+            .join("\n"))
+        );
+    }
+
+    #[rstest]
+    #[case("", "polygon needs at least 4 points but has 0")]
+    #[case(
+        "0,0\n0,2\n2,3\n2,0",
+        "points 1 (0,2) and 2 (2,3) are not on a horizontal or vertical line"
+    )]
+    fn test_reports_reject_invalid_polygons(#[case] input: &str, #[case] expected: &str) {
+        let expected = format!("ERROR: {}", expected);
+        verify!(top_rectangles, input, expected);
+        verify!(svg, input, expected);
+        verify!(debug, input, expected);
+    }
+
+    #[test]
+    fn example_svg() {
+        let points = parse_points(include_str!("example.txt").lines()).unwrap();
+        let rendered = render_polygon(&points);
+        let best = [(
+            "orange",
+            Rectangle {
+                corners: (Pos { x: 9, y: 5 }, Pos { x: 2, y: 3 }),
+                area: 24,
+            },
+        )];
+        let svg = encode_svg(&points, &rendered, &best);
+        let lines = svg.lines().collect_vec();
+        assert_eq!(
+            lines.first(),
+            Some(&r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="1 0 12 9">"#)
+        );
+        assert_eq!(
+            lines
+                .iter()
+                .filter(|line| line.contains(r#"fill="green""#))
+                .count(),
+            7
+        );
+        assert!(lines.contains(&r#"<rect x="2" y="3" width="10" height="1" fill="green"/>"#));
+        assert!(lines.contains(
+            &r#"<polygon points="7.5,1.5 11.5,1.5 11.5,7.5 9.5,7.5 9.5,5.5 2.5,5.5 2.5,3.5 7.5,3.5" fill="none" stroke="red" vector-effect="non-scaling-stroke"/>"#
+        ));
+        assert!(lines.contains(
+            &r#"<rect x="2" y="3" width="8" height="3" fill="none" stroke="orange" stroke-width="3" vector-effect="non-scaling-stroke"/>"#
+        ));
+        assert_eq!(lines.last(), Some(&"</svg>"));
+    }
+
+    #[test]
+    fn svg_left_of_and_above_the_origin() {
+        let points = parse_points("-3,-2\n1,-2\n1,0\n-3,0".lines()).unwrap();
+        let rendered = render_polygon(&points);
+        let svg = encode_svg(&points, &rendered, &[]);
+        let lines = svg.lines().collect_vec();
+        assert_eq!(
+            lines.first(),
+            Some(&r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-4 -3 7 5">"#)
+        );
+        assert!(lines.contains(
+            &r#"<polygon points="-2.5,-1.5 1.5,-1.5 1.5,0.5 -2.5,0.5" fill="none" stroke="red" vector-effect="non-scaling-stroke"/>"#
+        ));
+    }
+
+    #[test]
+    fn example_winding() {
+        let mut points = parse_points(include_str!("example.txt").lines()).unwrap();
+        assert_eq!(validate_polygon(&points), Ok(Winding::Clockwise));
+        points.reverse();
+        assert_eq!(validate_polygon(&points), Ok(Winding::Anticlockwise));
+    }
+
+    #[rstest]
+    #[case("0,0\n0,2\n2,2", "polygon needs at least 4 points but has 3")]
+    #[case(
+        "0,0\n0,2\n2,3\n2,0",
+        "points 1 (0,2) and 2 (2,3) are not on a horizontal or vertical line"
+    )]
+    #[case("0,0\n0,2\n2,2\n2,0\n0,0\n0,1", "points 0 and 4 are both at 0,0")]
+    #[case("0,0\n0,4\n4,4\n4,2\n-1,2\n-1,0", "edges 0-1 and 3-4 intersect")]
+    #[case("0,0\n0,4\n0,2\n2,2\n2,0", "edges 0-1 and 1-2 intersect")]
+    #[case(
+        "0,0\n0,2\n1,2\n1,0\n2,0\n2,2\n3,2\n3,0",
+        "edges 2-3 and 7-0 intersect"
+    )]
+    fn test_invalid_polygon(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(
+            validate_polygon(&parse_points(input.lines()).unwrap()),
+            Err(expected.to_string())
+        );
+        verify!(part2, input, format!("ERROR: {}", expected));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let input = "7,1\n11,1\n11;7\n";
+        verify!(
+            part2,
+            input,
+            "ERROR: line 3, column 1: invalid value '11;7': expected 2 comma separated coordinates but found 1 in '11;7'"
+        );
+        let input = "";
+        verify!(
+            part1,
+            input,
+            "ERROR: line 1, column 1: expected at least 2 red tiles"
+        );
+        let input = "3,4\n";
+        verify!(
+            part1,
+            input,
+            "ERROR: line 2, column 1: expected at least 2 red tiles"
+        );
+    }
+
+    #[test]
+    fn test_compressed_axis() {
+        let axis = CompressedAxis::new([7, 2, 11, 9, 7, 8].into_iter());
+        assert_eq!(
+            axis.slots,
+            vec![2..=2, 3..=6, 7..=7, 8..=8, 9..=9, 10..=10, 11..=11]
+        );
+        assert_eq!(axis.slot(9), 4);
+    }
+
+    #[derive(Debug)]
+    struct Column {
+        width: i64,
+        height: i64,
+    }
+
+    // columns standing on y = 0, flipped upside down or transposed, and with the points reversed
+    // and rotated to vary the winding and starting point
+    #[derive(Debug)]
+    struct Histogram {
+        columns: Vec<Column>,
+        flip: bool,
+        transpose: bool,
+        reverse: bool,
+        rotation: usize,
+    }
+
+    impl Histogram {
+        fn generate(rng: &mut impl Rng) -> Self {
+            let columns = (0..rng.random_range(1..6))
+                .map(|_| Column {
+                    width: rng.random_range(1..=4),
+                    height: rng.random_range(1..=6),
+                })
+                .collect_vec();
+            let mut histogram = Self {
+                columns,
+                flip: rng.random_bool(0.5),
+                transpose: rng.random_bool(0.5),
+                reverse: rng.random_bool(0.5),
+                rotation: 0,
+            };
+            histogram.rotation = rng.random_range(0..histogram.points().len());
+            histogram
+        }
+
+        // (start, end, height) of each column, in tiles before flipping or transposing
+        fn spans(&self) -> Vec<(i64, i64, i64)> {
+            let mut x = 0;
+            self.columns
+                .iter()
+                .map(|column| {
+                    x += column.width;
+                    (x - column.width, x, column.height)
+                })
+                .collect()
+        }
+
+        fn tile(&self, x: i64, y: i64) -> (i64, i64) {
+            let y = if self.flip { -y } else { y };
+            if self.transpose { (y, x) } else { (x, y) }
+        }
+
+        fn tile_inverse(&self, x: i64, y: i64) -> (i64, i64) {
+            let (x, y) = if self.transpose { (y, x) } else { (x, y) };
+            (x, if self.flip { -y } else { y })
+        }
+
+        fn points(&self) -> Vec<Pos> {
+            let spans = self.spans();
+            let mut points = vec![(spans[0].0, 0)];
+            let mut previous_height = 0;
+            for &(start, _, height) in &spans {
+                if height != previous_height {
+                    points.push((start, previous_height));
+                    points.push((start, height));
+                }
+                previous_height = height;
+            }
+            let end = spans.last().unwrap().1;
+            points.extend([(end, previous_height), (end, 0)]);
+            points.remove(0);
+            let mut points = points
+                .into_iter()
+                .map(|(x, y)| {
+                    let (x, y) = self.tile(x, y);
+                    Pos { x, y }
+                })
+                .collect_vec();
+            if self.reverse {
+                points.reverse();
+            }
+            points.rotate_left(self.rotation);
+            points
+        }
+
+        fn is_rect_valid(&self, a: &Pos, b: &Pos) -> bool {
+            let spans = self.spans();
+            let inside = |x: i64, y: i64| {
+                let (x, y) = self.tile_inverse(x, y);
+                y >= 0
+                    && spans
+                        .iter()
+                        .any(|&(start, end, height)| (start..=end).contains(&x) && y <= height)
+            };
+            (a.x.min(b.x)..=a.x.max(b.x))
+                .cartesian_product(a.y.min(b.y)..=a.y.max(b.y))
+                .all(|(x, y)| inside(x, y))
+        }
+    }
+
+    #[test]
+    fn rect_validity_matches_tiles() {
+        check_oracle!(
+            Histogram::generate,
+            |histogram: &Histogram| {
+                let points = histogram.points();
+                let rendered = render_polygon(&points);
+                points
+                    .iter()
+                    .tuple_combinations()
+                    .map(|(a, b)| rendered.is_rect_valid(a, b))
+                    .collect_vec()
+            },
+            |histogram: &Histogram| {
+                let points = histogram.points();
+                points
+                    .iter()
+                    .tuple_combinations()
+                    .map(|(a, b)| histogram.is_rect_valid(a, b))
+                    .collect_vec()
+            }
+        );
+    }
+
+    #[test]
+    fn histograms_are_valid_polygons() {
+        let mut rng = StdRng::seed_from_u64(2025);
+        for _ in 0..1000 {
+            let histogram = Histogram::generate(&mut rng);
+            let points = histogram.points();
+            if let Err(e) = validate_polygon(&points) {
+                panic!(
+                    "{:?} gives an invalid polygon {:?}: {}",
+                    histogram, points, e
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn largest_rectangles_match_sorted_areas() {
+        check_oracle!(
+            |rng| (Histogram::generate(rng), rng.random_range(1..10)),
+            |(histogram, k): &(Histogram, usize)| {
+                let points = histogram.points();
+                let rendered = render_polygon(&points);
+                largest_rectangles(&points, *k, |a, b| rendered.is_rect_valid(a, b))
+                    .into_iter()
+                    .map(|rectangle| rectangle.area)
+                    .collect_vec()
+            },
+            |(histogram, k): &(Histogram, usize)| {
+                let points = histogram.points();
+                points
+                    .iter()
+                    .tuple_combinations()
+                    .filter(|(a, b)| histogram.is_rect_valid(a, b))
+                    .map(|(a, b)| rectangle_area(a, b))
+                    .sorted()
+                    .rev()
+                    .take(*k)
+                    .collect_vec()
+            }
+        );
     }
 }
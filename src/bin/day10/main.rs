@@ -1,9 +1,10 @@
+use advent_of_code::parse::{self, ParseError, Span};
 use advent_of_code::{Named, Runner, create_runner, named};
 use core::iter::Iterator;
 use num::Integer;
 use std::{
     collections::{HashMap, VecDeque},
-    str::{FromStr, Lines},
+    str::Lines,
 };
 
 struct Button {
@@ -11,23 +12,26 @@ struct Button {
     mask: u16,
 }
 
-impl FromStr for Button {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.chars().nth(0).unwrap() != '(' {
-            Err(format!("incorrect enclosing characters in {s}"))
-        } else {
-            let lights = s[1..s.len() - 1]
-                .split(',')
-                .map(|n| n.parse::<u16>().expect("integer"))
-                .collect::<Vec<u16>>();
-            let mask = lights.iter().map(|light| 1 << light).fold(0, |a, b| a | b);
-            Ok(Self {
-                values: lights,
-                mask,
+impl Button {
+    // lights is the number of lights on the machine, which each value must index
+    fn parse(span: Span, lights: usize) -> Result<Self, ParseError> {
+        let values = span
+            .bracketed('(', ')')?
+            .split(',')
+            .map(|value| {
+                let light = value.parse::<u16>()?;
+                if usize::from(light) < lights {
+                    Ok(light)
+                } else {
+                    Err(value.error(format!(
+                        "light {} is out of range, the machine has {} lights",
+                        light, lights
+                    )))
+                }
             })
-        }
+            .collect::<Result<Vec<_>, _>>()?;
+        let mask = values.iter().map(|light| 1 << light).fold(0, |a, b| a | b);
+        Ok(Self { values, mask })
     }
 }
 
@@ -38,26 +42,49 @@ struct Machine {
 }
 
 impl Machine {
-    fn parse_target_lights(s: &str) -> u16 {
-        if s.chars().nth(0).unwrap() != '[' {
-            panic!("incorrect enclosing characters in {s}")
+    fn parse(line: Span) -> Result<Self, ParseError> {
+        let mut parts = line.split_whitespace();
+        let lights = parts
+            .next()
+            .ok_or_else(|| line.error("expected target lights"))?
+            .bracketed('[', ']')?;
+        let (target_lights, lights) = Machine::parse_target_lights(lights)?;
+        let joltage = parts
+            .next_back()
+            .ok_or_else(|| line.error("expected joltage requirements"))?;
+        let joltage_requirements = joltage.bracketed('{', '}')?.parse_list::<u16>(',')?;
+        if joltage_requirements.len() != lights {
+            return Err(joltage.error(format!(
+                "expected {} joltage requirements but found {}",
+                lights,
+                joltage_requirements.len()
+            )));
         }
-        s[1..s.len()-1].chars().enumerate().map(|(num, c)| match c {
-            '#' => 1,
-            '.' => 0,
-            _ => panic!("unexpected char {c}"),
-        } << num)
-        .fold(0, |a, b| a | b)
+        let buttons = parts
+            .map(|button| Button::parse(button, lights))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Machine {
+            target_lights,
+            buttons,
+            joltage_requirements,
+        })
     }
 
-    fn parse_joltage_requirements(s: &str) -> Vec<u16> {
-        if s.chars().nth(0).unwrap() != '{' {
-            panic!("incorrect enclosing characters in {s}")
+    // the lights as a mask, and how many there are
+    fn parse_target_lights(lights: Span) -> Result<(u16, usize), ParseError> {
+        if lights.text.len() > 16 {
+            return Err(lights.error("a machine can have at most 16 lights"));
         }
-        s[1..s.len() - 1]
-            .split(',')
-            .map(|n| n.parse::<u16>().expect("integer"))
-            .collect()
+        lights
+            .text
+            .chars()
+            .enumerate()
+            .try_fold(0, |mask, (num, c)| match c {
+                '#' => Ok(mask | 1 << num),
+                '.' => Ok(mask),
+                _ => Err(lights.error_at(num, format!("unexpected char {c}"))),
+            })
+            .map(|mask| (mask, lights.text.len()))
     }
 
     fn fewest_presses_lights(&self) -> usize {
@@ -103,24 +130,6 @@ impl Machine {
     }
 }
 
-impl FromStr for Machine {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s.split_whitespace();
-        let target_lights =
-            Machine::parse_target_lights(parts.next().ok_or("expected first element")?);
-        let joltage_requirements =
-            Machine::parse_joltage_requirements(parts.next_back().ok_or("expected last element")?);
-        let buttons = parts.map(|p| p.parse().unwrap()).collect::<Vec<_>>();
-        Ok(Machine {
-            target_lights,
-            buttons,
-            joltage_requirements,
-        })
-    }
-}
-
 struct MachineStateLights {
     lights: u16,
     presses: u16,
@@ -199,25 +208,29 @@ impl MachineStateJoltage {
     }
 }
 
-fn part1(input: Lines) -> String {
-    input
-        .map(|line| line.parse::<Machine>().expect("valid machine"))
+fn parse_machines(input: Lines) -> Result<Vec<Machine>, ParseError> {
+    parse::lines(input).map(Machine::parse).collect()
+}
+
+fn part1(input: Lines) -> Result<String, String> {
+    Ok(parse_machines(input)?
+        .iter()
         .map(|machine| machine.fewest_presses_lights())
         .sum::<usize>()
-        .to_string()
+        .to_string())
 }
 
-fn part2(input: Lines) -> String {
-    input
-        .map(|line| line.parse::<Machine>().expect("valid machine"))
+fn part2(input: Lines) -> Result<String, String> {
+    Ok(parse_machines(input)?
+        .iter()
         .map(|machine| machine.fewest_presses_joltage().unwrap())
         .sum::<usize>()
-        .to_string()
+        .to_string())
 }
 
 fn main() {
-    let input = include_str!("input.txt");
     let runner: &Runner = create_runner!();
+    let input = &runner.load(include_str!("input.txt"));
     runner.run(named!(part1), input);
     runner.run(named!(part2), input);
 }
@@ -225,23 +238,48 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use advent_of_code::verify;
+    use advent_of_code::examples;
+    use rstest::rstest;
 
-    #[test]
-    fn example() {
-        let input = include_str!("example.txt");
-        verify!(part1, input, "7");
-        verify!(part2, input, "33");
-    }
+    examples!();
 
     #[test]
     fn test_fewest_presses_lights() {
         let input = include_str!("example.txt");
-        let fewest: Vec<_> = input
-            .lines()
-            .map(|line| line.parse::<Machine>().expect("valid machine"))
+        let fewest: Vec<_> = parse_machines(input.lines())
+            .expect("valid machines")
+            .iter()
             .map(|machine| machine.fewest_presses_lights())
             .collect();
         assert_eq!(fewest, [2, 3, 2]);
     }
+
+    #[rstest]
+    #[case("[.#x] (0) {1,2,3}", "line 1, column 4: unexpected char x")]
+    #[case(
+        "[.##] (0,3) {1,2,3}",
+        "line 1, column 10: light 3 is out of range, the machine has 3 lights"
+    )]
+    #[case(
+        "[.##] (0,1 {1,2,3}",
+        "line 1, column 10: expected ')' at the end of '(0,1'"
+    )]
+    #[case(
+        "[.##] (0) {1,2}",
+        "line 1, column 11: expected 3 joltage requirements but found 2"
+    )]
+    #[case(
+        "[.##] (0) {1,two,3}",
+        "line 1, column 14: invalid value 'two': invalid digit found in string"
+    )]
+    #[case("(0) {1}", "line 1, column 1: expected '[' at the start of '(0)'")]
+    #[case("", "line 1, column 1: expected target lights")]
+    fn test_parse_errors(#[case] line: &str, #[case] expected: &str) {
+        assert_eq!(
+            Machine::parse(Span::new(line, 1))
+                .err()
+                .map(|e| e.to_string()),
+            Some(expected.to_string())
+        );
+    }
 }
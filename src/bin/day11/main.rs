@@ -1,10 +1,6 @@
+use advent_of_code::parse::{self, ParseError, Span};
 use advent_of_code::{Named, Runner, create_runner, named};
-use itertools::Itertools;
-use std::{
-    collections::HashMap,
-    iter::once,
-    str::{FromStr, Lines},
-};
+use std::{collections::HashMap, iter::once, str::Lines};
 
 #[derive(Debug, PartialEq)]
 struct Device {
@@ -12,17 +8,20 @@ struct Device {
     outputs: Vec<String>,
 }
 
-impl FromStr for Device {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (name, outputs) = s.split_once(':').ok_or(": after name")?;
-        let name = name.to_owned();
-        let outputs = outputs.split_whitespace().map(String::from).collect();
+impl Device {
+    // "ccc: ddd eee fff"
+    fn parse(line: Span) -> Result<Self, ParseError> {
+        let (name, outputs) = line.key_values(":")?;
+        let name = name.text.to_owned();
+        let outputs = outputs.iter().map(|o| o.text.to_owned()).collect();
         Ok(Self { name, outputs })
     }
 }
 
+fn parse_devices(input: Lines) -> Result<Vec<Device>, ParseError> {
+    parse::lines(input).map(Device::parse).collect()
+}
+
 struct Node {
     outputs: Vec<usize>,
 }
@@ -88,19 +87,15 @@ impl Graph {
     }
 }
 
-fn part1(input: Lines) -> String {
-    let devices = input
-        .map(|line| line.parse::<Device>().unwrap())
-        .collect_vec();
-    Graph::from_devices(&devices)
+fn part1(input: Lines) -> Result<String, String> {
+    let devices = parse_devices(input)?;
+    Ok(Graph::from_devices(&devices)
         .count_paths_name("you", "out")
-        .to_string()
+        .to_string())
 }
 
-fn part2(input: Lines) -> String {
-    let devices = input
-        .map(|line| line.parse::<Device>().unwrap())
-        .collect_vec();
+fn part2(input: Lines) -> Result<String, String> {
+    let devices = parse_devices(input)?;
     let graph = Graph::from_devices(&devices);
     let fft_first = graph.count_paths_name("svr", "fft")
         * graph.count_paths_name("fft", "dac")
@@ -108,12 +103,12 @@ fn part2(input: Lines) -> String {
     let dac_first = graph.count_paths_name("svr", "dac")
         * graph.count_paths_name("dac", "fft")
         * graph.count_paths_name("fft", "out");
-    (fft_first + dac_first).to_string()
+    Ok((fft_first + dac_first).to_string())
 }
 
 fn main() {
-    let input = include_str!("input.txt");
     let runner: &Runner = create_runner!();
+    let input = &runner.load(include_str!("input.txt"));
     runner.run(named!(part1), input);
     runner.run(named!(part2), input);
 }
@@ -121,28 +116,30 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use advent_of_code::verify;
+    use advent_of_code::examples;
 
-    #[test]
-    fn example1() {
-        let input = include_str!("example.txt");
-        verify!(part1, input, "5");
-    }
-
-    #[test]
-    fn example2() {
-        let input = include_str!("example2.txt");
-        verify!(part2, input, "2");
-    }
+    examples!();
 
     #[test]
     fn test_machine_parse() {
         assert_eq!(
-            "ccc: ddd eee fff".parse::<Device>(),
+            Device::parse(Span::new("ccc: ddd eee fff", 1)),
             Ok(Device {
                 name: "ccc".to_string(),
                 outputs: ["ddd", "eee", "fff"].map(|s| s.to_string()).to_vec()
             })
         )
     }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            part1("aaa: you\nyou out\n".lines()),
+            Err("line 2, column 1: expected ':' in 'you out'".to_string())
+        );
+        assert_eq!(
+            part1("aaa: you\n : out\n".lines()),
+            Err("line 2, column 1: expected a key before ':'".to_string())
+        );
+    }
 }
@@ -1,5 +1,5 @@
+use advent_of_code::parse::{self, ParseError, Span};
 use advent_of_code::{Named, Runner, create_runner, named};
-use itertools::Itertools;
 use std::str::Lines;
 
 struct Shape {
@@ -25,46 +25,78 @@ impl Region {
     }
 }
 
-fn parse_input(mut input: Lines) -> (Vec<Shape>, Vec<Region>) {
-    let input = input.by_ref();
-    let shapes = input
-        .take_while(|line| !line.contains("x"))
-        .chunks(4)
-        .into_iter()
-        .map(|chunk| {
-            let count = chunk
-                .map(|line| line.chars().filter(|c| *c == '#').count())
-                .sum();
-            Shape { count }
-        })
-        .collect();
-    let regions = input
-        .map(|line| {
-            let (dimensions, shapes) = line.split_once(": ").unwrap();
-            let (width, length) = dimensions.split_once('x').unwrap();
-            let width = width.parse().unwrap();
-            let length = length.parse().unwrap();
-            let shapes = shapes
-                .split_whitespace()
-                .map(|s| s.parse().unwrap())
-                .collect();
-            Region {
-                width,
-                length,
-                shapes,
+impl Shape {
+    // "0:" followed by rows of '#' and '.'
+    fn parse(index: usize, section: &[Span]) -> Result<Self, ParseError> {
+        let (header, rows) = section.split_first().expect("sections aren't empty");
+        let (key, values) = header.key_values(":")?;
+        if key.parse::<usize>()? != index || !values.is_empty() {
+            return Err(header.error(format!("expected shape header '{}:'", index)));
+        }
+        let mut count = 0;
+        for row in rows {
+            for (x, c) in row.text.chars().enumerate() {
+                match c {
+                    '#' => count += 1,
+                    '.' => (),
+                    _ => return Err(row.error_at(x, format!("unexpected char {c}"))),
+                }
             }
+        }
+        Ok(Shape { count })
+    }
+}
+
+impl Region {
+    // "12x5: 1 0 1 0 2 2", the size followed by how many of each shape
+    fn parse(line: Span, shapes: usize) -> Result<Self, ParseError> {
+        let (dimensions, counts) = line.key_values(":")?;
+        let (width, length) = dimensions.split_once("x")?;
+        let shapes_span = counts.first().copied().unwrap_or(line);
+        let counts = counts
+            .into_iter()
+            .map(Span::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        if counts.len() != shapes {
+            return Err(shapes_span.error(format!(
+                "expected {} shape counts but found {}",
+                shapes,
+                counts.len()
+            )));
+        }
+        Ok(Region {
+            width: width.parse()?,
+            length: length.parse()?,
+            shapes: counts,
         })
-        .collect();
-    (shapes, regions)
+    }
 }
 
-fn part1(input: Lines) -> String {
-    let (_shapes, regions) = parse_input(input);
-    regions
+// blank line separated shapes, then a section of regions
+fn parse_input(input: Lines) -> Result<(Vec<Shape>, Vec<Region>), ParseError> {
+    let sections = parse::sections(input);
+    let Some((regions, shapes)) = sections.split_last() else {
+        return Err(Span::new("", 1).error("expected shapes and regions"));
+    };
+    let shapes = shapes
+        .iter()
+        .enumerate()
+        .map(|(index, section)| Shape::parse(index, section))
+        .collect::<Result<Vec<_>, _>>()?;
+    let regions = regions
+        .iter()
+        .map(|&line| Region::parse(line, shapes.len()))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((shapes, regions))
+}
+
+fn part1(input: Lines) -> Result<String, String> {
+    let (_shapes, regions) = parse_input(input)?;
+    Ok(regions
         .iter()
         .filter(|r| r.available_shapes() >= r.required_shapes())
         .count()
-        .to_string()
+        .to_string())
 }
 
 fn part2(input: Lines) -> String {
@@ -72,8 +104,8 @@ fn part2(input: Lines) -> String {
 }
 
 fn main() {
-    let input = include_str!("input.txt");
     let runner: &Runner = create_runner!();
+    let input = &runner.load(include_str!("input.txt"));
     runner.run(named!(part1), input);
     runner.run(named!(part2), input);
 }
@@ -81,15 +113,45 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use advent_of_code::verify;
+    use advent_of_code::examples;
+    use rstest::rstest;
+
+    examples!();
 
     #[test]
-    fn example() {
+    fn test_parse_input() {
         let input = include_str!("example.txt");
-        // the actual answer for the example is 2
-        // example is harder than real input since it can't be done by
-        // assuming every shape is a 3x3 rectangle
-        verify!(part1, input, "0");
-        verify!(part2, input, "0");
+        let (shapes, regions) = parse_input(input.lines()).unwrap();
+        assert_eq!(
+            shapes.iter().map(|shape| shape.count).collect::<Vec<_>>(),
+            [7, 7, 7, 7, 7, 7]
+        );
+        assert_eq!(
+            regions
+                .iter()
+                .map(|region| (region.width, region.length, region.required_shapes()))
+                .collect::<Vec<_>>(),
+            [(4, 4, 2), (12, 5, 6), (12, 5, 7)]
+        );
+    }
+
+    #[rstest]
+    #[case(
+        "0:\n#.\n\n2:\n.#\n\n4x4: 1 1\n",
+        "line 4, column 1: expected shape header '1:'"
+    )]
+    #[case("0:\n#o\n\n4x4: 1\n", "line 2, column 2: unexpected char o")]
+    #[case(
+        "0:\n#.\n\n4x4: 1 2\n",
+        "line 4, column 6: expected 1 shape counts but found 2"
+    )]
+    #[case("0:\n#.\n\n4by4: 1\n", "line 4, column 1: expected 'x' in '4by4'")]
+    #[case(
+        "0:\n#.\n\n4x4: one\n",
+        "line 4, column 6: invalid value 'one': invalid digit found in string"
+    )]
+    #[case("", "line 1, column 1: expected shapes and regions")]
+    fn test_parse_errors(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(part1(input.lines()), Err(expected.to_string()));
     }
 }
@@ -10,8 +10,8 @@ fn part2(input: Lines) -> String {
 }
 
 fn main() {
-    let input = include_str!("input.txt");
     let runner: &Runner = create_runner!();
+    let input = &runner.load(include_str!("input.txt"));
     runner.run(named!(part1), input);
     runner.run(named!(part2), input);
 }
@@ -19,12 +19,7 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use advent_of_code::verify;
+    use advent_of_code::examples;
 
-    #[test]
-    fn example() {
-        let input = include_str!("example.txt");
-        verify!(part1, input, "0");
-        verify!(part2, input, "0");
-    }
+    examples!();
 }
@@ -0,0 +1,376 @@
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Layout {
+    // lines of any length
+    Lines,
+    // every line the same length. with padding, short lines are padded out to the longest,
+    // e.g. for columns which have lost their trailing spaces
+    Rectangular { padding: Option<char> },
+}
+
+// what a day declares about its input, checked before any operation sees it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InputFormat {
+    pub layout: Layout,
+    pub normalise: bool,
+}
+
+impl InputFormat {
+    pub const fn new(layout: Layout) -> Self {
+        Self {
+            layout,
+            normalise: true,
+        }
+    }
+
+    // report problems but pass the input through unchanged
+    pub const fn warn_only(self) -> Self {
+        Self {
+            normalise: false,
+            ..self
+        }
+    }
+}
+
+impl Default for InputFormat {
+    fn default() -> Self {
+        Self::new(Layout::Lines)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Issue {
+    ByteOrderMark,
+    Crlf {
+        lines: usize,
+    },
+    TrailingBlankLines {
+        lines: usize,
+    },
+    // line is the first of the lines, counting from 1
+    TrailingWhitespace {
+        lines: usize,
+        line: usize,
+    },
+    MissingFinalNewline,
+    // line is the first of the short lines, counting from 1
+    Ragged {
+        lines: usize,
+        line: usize,
+        width: usize,
+    },
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::ByteOrderMark => write!(f, "input starts with a byte order mark"),
+            Issue::Crlf { lines } => write!(f, "{} lines end with CRLF", lines),
+            Issue::TrailingBlankLines { lines } => {
+                write!(f, "input ends with {} blank lines", lines)
+            }
+            Issue::TrailingWhitespace { lines, line } => write!(
+                f,
+                "{} lines end with whitespace, starting with line {}",
+                lines, line
+            ),
+            Issue::MissingFinalNewline => write!(f, "input does not end with a newline"),
+            Issue::Ragged { lines, line, width } => write!(
+                f,
+                "{} lines are shorter than {} characters, starting with line {}",
+                lines, width, line
+            ),
+        }
+    }
+}
+
+// what became of an issue once the input was prepared
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    Normalised,
+    Warning,
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Normalised => write!(f, "normalised"),
+            Outcome::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+// blank lines at the end are ignored when looking for ragged lines
+fn content_lines(input: &str) -> Vec<&str> {
+    let mut lines = input
+        .strip_prefix('\u{feff}')
+        .unwrap_or(input)
+        .lines()
+        .collect::<Vec<_>>();
+    while lines.last().is_some_and(|line| is_blank(line)) {
+        lines.pop();
+    }
+    lines
+}
+
+fn padding(format: InputFormat) -> Option<char> {
+    match format.layout {
+        Layout::Rectangular { padding } => padding,
+        Layout::Lines => None,
+    }
+}
+
+// trailing padding is part of the layout rather than stray whitespace
+fn trim_end(line: &str, padding: Option<char>) -> &str {
+    line.trim_end_matches(|c: char| c.is_whitespace() && Some(c) != padding)
+}
+
+fn width(lines: &[&str]) -> usize {
+    lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0)
+}
+
+pub fn check(input: &str, format: InputFormat) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    if input.starts_with('\u{feff}') {
+        issues.push(Issue::ByteOrderMark);
+    }
+    let crlf = input.matches("\r\n").count();
+    if crlf > 0 {
+        issues.push(Issue::Crlf { lines: crlf });
+    }
+    let lines = content_lines(input);
+    let blank = input.lines().count() - lines.len();
+    if blank > 0 {
+        issues.push(Issue::TrailingBlankLines { lines: blank });
+    }
+    let padding = padding(format);
+    let untrimmed = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| trim_end(line, padding).len() < line.len())
+        .map(|(i, _)| i + 1)
+        .collect::<Vec<_>>();
+    if let Some(&line) = untrimmed.first() {
+        issues.push(Issue::TrailingWhitespace {
+            lines: untrimmed.len(),
+            line,
+        });
+    }
+    if blank == 0 && !lines.is_empty() && !input.ends_with('\n') {
+        issues.push(Issue::MissingFinalNewline);
+    }
+    let lines = lines
+        .into_iter()
+        .map(|line| trim_end(line, padding))
+        .collect::<Vec<_>>();
+    if let Layout::Rectangular { .. } = format.layout {
+        let width = width(&lines);
+        let short = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.chars().count() < width)
+            .map(|(i, _)| i + 1)
+            .collect::<Vec<_>>();
+        if let Some(&line) = short.first() {
+            issues.push(Issue::Ragged {
+                lines: short.len(),
+                line,
+                width,
+            });
+        }
+    }
+    issues
+}
+
+// fixes whatever check finds, apart from ragged lines in a layout without padding.
+// the input is borrowed unchanged when there is nothing to fix
+pub fn normalise(input: &str, format: InputFormat) -> Cow<'_, str> {
+    let issues = check(input, format);
+    let padding = padding(format);
+    let fixable = issues.iter().any(|issue| match issue {
+        Issue::Ragged { .. } => padding.is_some(),
+        _ => true,
+    });
+    if !fixable {
+        return Cow::Borrowed(input);
+    }
+    let lines = content_lines(input)
+        .into_iter()
+        .map(|line| trim_end(line, padding))
+        .collect::<Vec<_>>();
+    let width = width(&lines);
+    let mut normalised = String::with_capacity(input.len());
+    for line in lines {
+        normalised.push_str(line);
+        if let Some(padding) = padding {
+            normalised.extend(std::iter::repeat_n(padding, width - line.chars().count()));
+        }
+        normalised.push('\n');
+    }
+    Cow::Owned(normalised)
+}
+
+// normalises the input if the format asks for it, pairing each issue found with whether it was
+// fixed or is still there
+pub fn prepare(input: &str, format: InputFormat) -> (Cow<'_, str>, Vec<(Issue, Outcome)>) {
+    let issues = check(input, format);
+    let input = if format.normalise {
+        normalise(input, format)
+    } else {
+        Cow::Borrowed(input)
+    };
+    let remaining = check(&input, format);
+    let outcomes = issues
+        .into_iter()
+        .map(|issue| {
+            let outcome = if remaining.contains(&issue) {
+                Outcome::Warning
+            } else {
+                Outcome::Normalised
+            };
+            (issue, outcome)
+        })
+        .collect();
+    (input, outcomes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLUMNS: InputFormat = InputFormat::new(Layout::Rectangular { padding: Some(' ') });
+
+    #[test]
+    fn test_clean_input() {
+        let input = "12 3\n45 6\n*  +\n";
+        assert_eq!(check(input, COLUMNS), vec![]);
+        assert!(matches!(normalise(input, COLUMNS), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_mangled_input() {
+        let input = "\u{feff}12 3\r\n45\r\n*  +\r\n\r\n  \n";
+        assert_eq!(
+            check(input, COLUMNS),
+            vec![
+                Issue::ByteOrderMark,
+                Issue::Crlf { lines: 4 },
+                Issue::TrailingBlankLines { lines: 2 },
+                Issue::Ragged {
+                    lines: 1,
+                    line: 2,
+                    width: 4
+                },
+            ]
+        );
+        assert_eq!(normalise(input, COLUMNS), "12 3\n45  \n*  +\n");
+        assert_eq!(normalise(input, InputFormat::default()), "12 3\n45\n*  +\n");
+    }
+
+    #[test]
+    fn test_ragged_grid_is_left_alone() {
+        let grid = InputFormat::new(Layout::Rectangular { padding: None });
+        let input = "..@\n.@\n";
+        assert_eq!(
+            check(input, grid),
+            vec![Issue::Ragged {
+                lines: 1,
+                line: 2,
+                width: 3
+            }]
+        );
+        assert_eq!(normalise(input, grid), input);
+        assert_eq!(check(input, InputFormat::default()), vec![]);
+    }
+
+    #[test]
+    fn test_trailing_whitespace() {
+        let input = "3-5 \n10-14\n\t\n1\t \n";
+        assert_eq!(
+            check(input, InputFormat::default()),
+            vec![Issue::TrailingWhitespace { lines: 3, line: 1 }]
+        );
+        assert_eq!(
+            normalise(input, InputFormat::default()),
+            "3-5\n10-14\n\n1\n"
+        );
+        // padding at the end of a line is part of the layout, anything else isn't
+        let input = "12 3\n4   \n*  +\t\n";
+        assert_eq!(
+            check(input, COLUMNS),
+            vec![Issue::TrailingWhitespace { lines: 1, line: 3 }]
+        );
+        assert_eq!(normalise(input, COLUMNS), "12 3\n4   \n*  +\n");
+    }
+
+    #[test]
+    fn test_missing_final_newline() {
+        let input = "12 3\n45 6\n*  +";
+        assert_eq!(check(input, COLUMNS), vec![Issue::MissingFinalNewline]);
+        assert_eq!(normalise(input, COLUMNS), "12 3\n45 6\n*  +\n");
+        assert_eq!(check("", COLUMNS), vec![]);
+    }
+
+    #[test]
+    fn test_prepare() {
+        let grid = InputFormat::new(Layout::Rectangular { padding: None });
+        let input = "..@\r\n.@\r\n";
+        let (prepared, outcomes) = prepare(input, grid);
+        assert_eq!(prepared, "..@\n.@\n");
+        let ragged = Issue::Ragged {
+            lines: 1,
+            line: 2,
+            width: 3,
+        };
+        assert_eq!(
+            outcomes,
+            vec![
+                (Issue::Crlf { lines: 2 }, Outcome::Normalised),
+                (ragged, Outcome::Warning),
+            ]
+        );
+        let (prepared, outcomes) = prepare(input, grid.warn_only());
+        assert!(matches!(prepared, Cow::Borrowed(_)));
+        assert!(
+            outcomes
+                .iter()
+                .all(|(_, outcome)| *outcome == Outcome::Warning)
+        );
+        assert_eq!(outcomes.len(), 2);
+    }
+
+    #[test]
+    fn test_issue_messages() {
+        assert_eq!(
+            Issue::Ragged {
+                lines: 3,
+                line: 2,
+                width: 15
+            }
+            .to_string(),
+            "3 lines are shorter than 15 characters, starting with line 2"
+        );
+        assert_eq!(
+            Issue::TrailingBlankLines { lines: 2 }.to_string(),
+            "input ends with 2 blank lines"
+        );
+        assert_eq!(
+            Issue::TrailingWhitespace { lines: 4, line: 7 }.to_string(),
+            "4 lines end with whitespace, starting with line 7"
+        );
+        assert_eq!(
+            Issue::MissingFinalNewline.to_string(),
+            "input does not end with a newline"
+        );
+    }
+}
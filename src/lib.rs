@@ -1,4 +1,10 @@
-use std::{collections::HashSet, str::Lines};
+pub mod input;
+pub mod parse;
+pub mod property;
+pub mod vector;
+
+use input::InputFormat;
+use std::{borrow::Cow, collections::HashSet, fmt::Display, str::Lines};
 
 pub struct Named<T> {
     pub wrapped: T,
@@ -21,27 +27,68 @@ macro_rules! named {
 pub struct Runner {
     module_name: &'static str,
     operations: HashSet<String>,
+    format: InputFormat,
+}
+
+type Operation<A> = fn(Lines) -> A;
+
+// what an operation returns, either a plain answer or one which can fail
+pub trait Answer {
+    fn into_output(self) -> String;
+}
+
+impl Answer for String {
+    fn into_output(self) -> String {
+        self
+    }
 }
 
-type Operation = fn(Lines) -> String;
+impl<E: Display> Answer for Result<String, E> {
+    fn into_output(self) -> String {
+        self.unwrap_or_else(|e| format!("ERROR: {}", e))
+    }
+}
 
 impl Runner {
     pub fn create(module_name: &'static str) -> Self {
         Self {
             module_name,
             operations: std::env::args().skip(1).collect(),
+            format: InputFormat::default(),
+        }
+    }
+
+    pub fn with_format(self, format: InputFormat) -> Self {
+        Self { format, ..self }
+    }
+
+    // checks the input against the day's declared format before any operation sees it,
+    // warnings go to stderr so they don't get mixed up with the answers
+    pub fn load<'a>(&self, input: &'a str) -> Cow<'a, str> {
+        let (input, outcomes) = input::prepare(input, self.format);
+        for (issue, outcome) in outcomes {
+            eprintln!("{} input {}: {}", self.module_name, outcome, issue);
         }
+        input
     }
 
-    pub fn run(&self, op: &Named<Operation>, input: &str) {
+    pub fn run<A: Answer>(&self, op: &Named<Operation<A>>, input: &str) {
         let enabled: bool = self.operations.is_empty() || self.operations.contains(op.name);
         let result: String = if enabled {
-            (op.wrapped)(input.lines())
+            (op.wrapped)(input.lines()).into_output()
         } else {
             String::from("(DISABLED)")
         };
         println!("{} {}:\n{}", self.module_name, op.name, result);
     }
+
+    // for reports and debug output which only run when named on the command line
+    pub fn run_if_requested<A: Answer>(&self, op: &Named<Operation<A>>, input: &str) {
+        if self.operations.contains(op.name) {
+            let result = (op.wrapped)(input.lines()).into_output();
+            println!("{} {}:\n{}", self.module_name, op.name, result);
+        }
+    }
 }
 
 #[macro_export]
@@ -49,12 +96,15 @@ macro_rules! create_runner {
     () => {
         &Runner::create(module_path!())
     };
+    ($format:expr) => {
+        &Runner::create(module_path!()).with_format($format)
+    };
 }
 
 #[macro_export]
 macro_rules! verify {
     ( $op:ident, $input:ident, $expected:expr ) => {{
-        let result = $op($input.lines());
+        let result = $crate::Answer::into_output($op($input.lines()));
         assert_eq!(
             result,
             $expected,
@@ -64,3 +114,19 @@ macro_rules! verify {
         );
     }};
 }
+
+// one test per operation listed in each example's .expected file, see build.rs
+#[macro_export]
+macro_rules! examples {
+    () => {
+        mod examples {
+            use super::*;
+            include!(concat!(
+                env!("OUT_DIR"),
+                "/examples/",
+                env!("CARGO_BIN_NAME"),
+                ".rs"
+            ));
+        }
+    };
+}
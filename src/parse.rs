@@ -0,0 +1,267 @@
+use std::fmt::{self, Display};
+use std::str::{FromStr, Lines};
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl From<ParseError> for String {
+    fn from(error: ParseError) -> Self {
+        error.to_string()
+    }
+}
+
+// a piece of the input which remembers where it came from, so errors can point at it.
+// lines and columns start at 1
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Span<'a> {
+    pub text: &'a str,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl<'a> Span<'a> {
+    pub fn new(text: &'a str, line: usize) -> Self {
+        Self {
+            text,
+            line,
+            column: 1,
+        }
+    }
+
+    pub fn error(&self, message: impl Display) -> ParseError {
+        self.error_at(0, message)
+    }
+
+    // offset is in characters from the start of the span
+    pub fn error_at(&self, offset: usize, message: impl Display) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.column + offset,
+            message: message.to_string(),
+        }
+    }
+
+    // sub must be a slice of this span's text
+    fn sub(&self, sub: &'a str) -> Span<'a> {
+        let offset = sub.as_ptr() as usize - self.text.as_ptr() as usize;
+        Span {
+            text: sub,
+            line: self.line,
+            column: self.column + self.text[..offset].chars().count(),
+        }
+    }
+
+    pub fn trim(self) -> Span<'a> {
+        self.sub(self.text.trim())
+    }
+
+    pub fn split(self, delimiter: char) -> impl DoubleEndedIterator<Item = Span<'a>> {
+        self.text.split(delimiter).map(move |part| self.sub(part))
+    }
+
+    pub fn split_whitespace(self) -> impl DoubleEndedIterator<Item = Span<'a>> {
+        self.text.split_whitespace().map(move |part| self.sub(part))
+    }
+
+    pub fn split_once(self, delimiter: &str) -> Result<(Span<'a>, Span<'a>), ParseError> {
+        let (before, after) = self
+            .text
+            .split_once(delimiter)
+            .ok_or_else(|| self.error(format!("expected '{}' in '{}'", delimiter, self.text)))?;
+        Ok((self.sub(before), self.sub(after)))
+    }
+
+    // the text between open and close, which must be the first and last characters
+    pub fn bracketed(self, open: char, close: char) -> Result<Span<'a>, ParseError> {
+        let inner = self.text.strip_prefix(open).ok_or_else(|| {
+            self.error(format!(
+                "expected '{}' at the start of '{}'",
+                open, self.text
+            ))
+        })?;
+        let inner = inner.strip_suffix(close).ok_or_else(|| {
+            let end = self.text.chars().count().saturating_sub(1);
+            self.error_at(
+                end,
+                format!("expected '{}' at the end of '{}'", close, self.text),
+            )
+        })?;
+        Ok(self.sub(inner))
+    }
+
+    pub fn parse<T>(self) -> Result<T, ParseError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.parse_with(str::parse)
+    }
+
+    // for types which can't implement FromStr, such as glam's vectors
+    pub fn parse_with<T, E: Display>(
+        self,
+        parse: impl FnOnce(&'a str) -> Result<T, E>,
+    ) -> Result<T, ParseError> {
+        parse(self.text).map_err(|e| self.error(format!("invalid value '{}': {}", self.text, e)))
+    }
+
+    pub fn parse_list<T>(self, delimiter: char) -> Result<Vec<T>, ParseError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.split(delimiter).map(Span::parse).collect()
+    }
+
+    // "key: a b c", with the key trimmed and the values separated by whitespace
+    pub fn key_values(self, separator: &str) -> Result<(Span<'a>, Vec<Span<'a>>), ParseError> {
+        let (key, values) = self.split_once(separator)?;
+        let key = key.trim();
+        if key.text.is_empty() {
+            return Err(key.error(format!("expected a key before '{}'", separator)));
+        }
+        Ok((key, values.split_whitespace().collect()))
+    }
+}
+
+pub fn lines(input: Lines<'_>) -> impl Iterator<Item = Span<'_>> {
+    input.enumerate().map(|(i, line)| Span::new(line, i + 1))
+}
+
+// groups of lines separated by blank lines
+pub fn sections(input: Lines<'_>) -> Vec<Vec<Span<'_>>> {
+    let mut sections = vec![Vec::new()];
+    for line in lines(input) {
+        match sections.last_mut() {
+            Some(section) if !line.text.is_empty() => section.push(line),
+            _ => sections.push(Vec::new()),
+        }
+    }
+    sections.retain(|section| !section.is_empty());
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(line: usize, column: usize, message: &str) -> ParseError {
+        ParseError {
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_split_positions() {
+        let span = Span::new("ab: 12 3", 4);
+        let (key, values) = span.key_values(":").unwrap();
+        assert_eq!(
+            key,
+            Span {
+                text: "ab",
+                line: 4,
+                column: 1
+            }
+        );
+        assert_eq!(
+            values,
+            vec![
+                Span {
+                    text: "12",
+                    line: 4,
+                    column: 5
+                },
+                Span {
+                    text: "3",
+                    line: 4,
+                    column: 8
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_list() {
+        let span = Span::new("[.#] (1,3,x) {3,5}", 2);
+        let groups = span.split_whitespace().collect::<Vec<_>>();
+        assert_eq!(groups[0].bracketed('[', ']').unwrap().text, ".#");
+        assert_eq!(
+            groups[2]
+                .bracketed('{', '}')
+                .unwrap()
+                .parse_list::<u16>(','),
+            Ok(vec![3, 5])
+        );
+        assert_eq!(
+            groups[1]
+                .bracketed('(', ')')
+                .unwrap()
+                .parse_list::<u16>(','),
+            Err(error(
+                2,
+                11,
+                "invalid value 'x': invalid digit found in string"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let span = Span::new("(1,2", 3);
+        assert_eq!(
+            span.bracketed('[', ']'),
+            Err(error(3, 1, "expected '[' at the start of '(1,2'"))
+        );
+        assert_eq!(
+            span.bracketed('(', ')'),
+            Err(error(3, 4, "expected ')' at the end of '(1,2'"))
+        );
+        assert_eq!(
+            Span::new(" : a", 1).key_values(":"),
+            Err(error(1, 1, "expected a key before ':'"))
+        );
+        assert_eq!(
+            Span::new("a b", 1).key_values(":"),
+            Err(error(1, 1, "expected ':' in 'a b'"))
+        );
+        assert_eq!(String::from(error(5, 2, "oops")), "line 5, column 2: oops");
+    }
+
+    #[test]
+    fn test_sections() {
+        let input = "0:\n#.\n\n\n1:\n.#\n\n4x4: 1 2\n";
+        let sections = sections(input.lines())
+            .into_iter()
+            .map(|section| {
+                section
+                    .iter()
+                    .map(|line| (line.line, line.text))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sections,
+            vec![
+                vec![(1, "0:"), (2, "#.")],
+                vec![(5, "1:"), (6, ".#")],
+                vec![(8, "4x4: 1 2")],
+            ]
+        );
+    }
+}
@@ -0,0 +1,295 @@
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use std::cell::Cell;
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+pub const DEFAULT_CASES: usize = 1000;
+const DEFAULT_SEED: u64 = 2025;
+const MAX_SHRINK_RUNS: usize = 20_000;
+
+// the random words a generator consumed. a failing case is shrunk by simplifying its words and
+// running the generator again, so every shrunk input is one the generator could have produced.
+// words past the end of a replayed sequence are zero
+pub struct Choices {
+    words: Vec<u64>,
+    used: usize,
+    source: Option<StdRng>,
+}
+
+impl Choices {
+    fn record(seed: u64) -> Self {
+        Self {
+            words: Vec::new(),
+            used: 0,
+            source: Some(StdRng::seed_from_u64(seed)),
+        }
+    }
+
+    fn replay(words: Vec<u64>) -> Self {
+        Self {
+            words,
+            used: 0,
+            source: None,
+        }
+    }
+
+    // the words which were actually drawn, including any zeros drawn past the end
+    fn into_words(mut self) -> Vec<u64> {
+        self.words.resize(self.used, 0);
+        self.words
+    }
+}
+
+impl RngCore for Choices {
+    // the high half, so that a smaller word still gives a smaller value
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        if self.used == self.words.len() {
+            let word = self.source.as_mut().map_or(0, |source| source.next_u64());
+            self.words.push(word);
+        }
+        self.used += 1;
+        self.words[self.used - 1]
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        for chunk in dst.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+thread_local! {
+    static QUIET: Cell<bool> = const { Cell::new(false) };
+}
+
+// runs f, catching any panic without printing it. other threads' panics are still reported
+fn quietly<R>(f: impl FnOnce() -> R) -> Option<R> {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let report = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !QUIET.with(Cell::get) {
+                report(info);
+            }
+        }));
+    });
+    QUIET.with(|quiet| quiet.set(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    QUIET.with(|quiet| quiet.set(false));
+    result.ok()
+}
+
+// shorter sequences are simpler, then sequences with smaller words earlier on. only accepting
+// simpler sequences means shrinking always finishes
+fn simpler(a: &[u64], b: &[u64]) -> bool {
+    (a.len(), a) < (b.len(), b)
+}
+
+// tries deleting each word, then lowers each word as far as it will go while the case still
+// fails. runs which panic are outside the domain of the functions under test, so they're skipped
+fn shrink_failure<T, Generate, Fails>(mut words: Vec<u64>, generate: Generate, fails: Fails) -> T
+where
+    Generate: Fn(&mut Choices) -> T,
+    Fails: Fn(&T) -> bool,
+{
+    let mut runs = 0;
+    let mut attempt = |words: &mut Vec<u64>, candidate: Vec<u64>| {
+        if runs == MAX_SHRINK_RUNS {
+            return false;
+        }
+        runs += 1;
+        let shrunk = quietly(|| {
+            let mut choices = Choices::replay(candidate);
+            let input = generate(&mut choices);
+            fails(&input).then(|| choices.into_words())
+        })
+        .flatten()
+        .filter(|shrunk| simpler(shrunk, words));
+        let improved = shrunk.is_some();
+        if let Some(shrunk) = shrunk {
+            *words = shrunk;
+        }
+        improved
+    };
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in (0..words.len()).rev() {
+            if i < words.len() {
+                let mut candidate = words.clone();
+                candidate.remove(i);
+                improved |= attempt(&mut words, candidate);
+            }
+        }
+        for i in 0..words.len() {
+            // the smallest failing value lies above lower, which passes, and up to words[i]
+            let mut lower = None;
+            while i < words.len() {
+                let middle = match lower {
+                    None if words[i] > 0 => 0,
+                    Some(lower) if words[i] - lower > 1 => lower + (words[i] - lower) / 2,
+                    _ => break,
+                };
+                let mut candidate = words.clone();
+                candidate[i] = middle;
+                if attempt(&mut words, candidate) {
+                    improved = true;
+                } else {
+                    lower = Some(middle);
+                }
+            }
+        }
+    }
+    generate(&mut Choices::replay(words))
+}
+
+// compares an optimised function against a brute force oracle on randomly generated inputs,
+// panicking with the smallest failing input found by shrinking
+pub fn check_oracle<T, R, Generate, Optimised, Oracle>(
+    name: &str,
+    cases: usize,
+    generate: Generate,
+    optimised: Optimised,
+    oracle: Oracle,
+) where
+    T: Debug,
+    R: Debug + PartialEq,
+    Generate: Fn(&mut Choices) -> T,
+    Optimised: Fn(&T) -> R,
+    Oracle: Fn(&T) -> R,
+{
+    let mut seeds = StdRng::seed_from_u64(DEFAULT_SEED);
+    let fails = |input: &T| optimised(input) != oracle(input);
+    for case in 0..cases {
+        let mut choices = Choices::record(seeds.next_u64());
+        let input = generate(&mut choices);
+        if fails(&input) {
+            let shrunk = shrink_failure(choices.into_words(), &generate, fails);
+            panic!(
+                "{} disagrees with oracle on case {} (seed {})\n\
+                 original input: {:?}\n\
+                 shrunk input: {:?}\n\
+                 optimised: {:?}\n\
+                 oracle: {:?}",
+                name,
+                case,
+                DEFAULT_SEED,
+                input,
+                shrunk,
+                optimised(&shrunk),
+                oracle(&shrunk)
+            );
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! check_oracle {
+    ( $generate:expr, $optimised:expr, $oracle:expr ) => {
+        $crate::check_oracle!(
+            $generate,
+            $optimised,
+            $oracle,
+            $crate::property::DEFAULT_CASES
+        )
+    };
+    ( $generate:expr, $optimised:expr, $oracle:expr, $cases:expr ) => {
+        $crate::property::check_oracle(
+            &format!("{} {}", module_path!(), std::stringify!($optimised)),
+            $cases,
+            $generate,
+            $optimised,
+            $oracle,
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    fn random_list(rng: &mut Choices) -> Vec<u32> {
+        let len = rng.random_range(0..8);
+        (0..len).map(|_| rng.random_range(0..100)).collect()
+    }
+
+    fn recorded<T>(seed: u64, generate: impl Fn(&mut Choices) -> T) -> (T, Vec<u64>) {
+        let mut choices = Choices::record(seed);
+        let input = generate(&mut choices);
+        (input, choices.into_words())
+    }
+
+    #[test]
+    fn test_replay() {
+        let (list, words) = recorded(7, random_list);
+        assert_eq!(random_list(&mut Choices::replay(words.clone())), list);
+        assert_eq!(random_list(&mut Choices::replay(Vec::new())), vec![]);
+        let mut halved = words;
+        halved[0] /= 2;
+        assert!(random_list(&mut Choices::replay(halved)).len() <= list.len());
+    }
+
+    #[test]
+    fn test_shrink_failure() {
+        let fails = |v: &Vec<u32>| v.iter().any(|&n| n >= 10);
+        let words = (0..)
+            .map(|seed| recorded(seed, random_list))
+            .find(|(list, _)| list.len() > 3 && fails(list))
+            .unwrap()
+            .1;
+        assert_eq!(shrink_failure(words, random_list, fails), vec![10]);
+    }
+
+    // the generator only makes pairs with a < b, and shrinking must not break that
+    #[test]
+    fn test_shrink_keeps_generator_invariants() {
+        let generate = |rng: &mut Choices| {
+            let b = rng.random_range(1..1000u32);
+            (rng.random_range(0..b), b)
+        };
+        let impossible = Cell::new(0);
+        let fails = |&(a, b): &(u32, u32)| {
+            if a >= b {
+                impossible.set(impossible.get() + 1);
+                return true;
+            }
+            b - a > 5
+        };
+        let words = recorded(3, generate).1;
+        let (a, b) = shrink_failure(words, generate, fails);
+        assert_eq!(impossible.get(), 0);
+        assert_eq!(b - a, 6);
+    }
+
+    #[test]
+    fn test_shrink_failure_skips_panics() {
+        let generate = |rng: &mut Choices| (rng.random_range(0..100u32), rng.random_range(0..10));
+        let fails = |&(a, b): &(u32, u32)| a / b > 2;
+        let words = (0..)
+            .map(|seed| recorded(seed, generate))
+            .find(|&((a, b), _)| b > 0 && a / b > 2)
+            .unwrap()
+            .1;
+        assert_eq!(shrink_failure(words, generate, fails), (3, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "shrunk input: 100")]
+    fn test_check_oracle_disagrees() {
+        check_oracle(
+            "saturating",
+            DEFAULT_CASES,
+            |rng| rng.random_range(0u32..1000),
+            |&n| n.min(99),
+            |&n| n,
+        );
+    }
+}
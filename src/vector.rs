@@ -0,0 +1,151 @@
+use glam::{I64Vec2, I64Vec3, IVec2, IVec3};
+use std::fmt::{self, Display};
+
+// glam's integer vectors already have arithmetic, manhattan_distance and chebyshev_distance,
+// this adds what the puzzles need on top, generically over the vector types
+pub trait Vector: Copy + PartialEq {
+    type Scalar: Display;
+
+    // from "x,y" or "x,y,z" as found in puzzle inputs
+    fn parse(s: &str) -> Result<Self, String>;
+
+    fn components(self) -> impl Iterator<Item = Self::Scalar>;
+
+    fn manhattan(self, other: Self) -> u64;
+
+    fn chebyshev(self, other: Self) -> u64;
+
+    // widened, glam's distance_squared overflows i32 with coordinates in the hundreds of thousands
+    fn euclidean_squared(self, other: Self) -> u128;
+
+    fn component_min(self, other: Self) -> Self;
+
+    fn component_max(self, other: Self) -> Self;
+}
+
+macro_rules! impl_vector {
+    ( $( $t:ty: $scalar:ty, $dimensions:literal );* ) => {
+        $(
+            impl Vector for $t {
+                type Scalar = $scalar;
+
+                fn parse(s: &str) -> Result<Self, String> {
+                    let values = s.split(',').collect::<Vec<_>>();
+                    if values.len() != $dimensions {
+                        return Err(format!(
+                            "expected {} comma separated coordinates but found {} in '{}'",
+                            $dimensions,
+                            values.len(),
+                            s
+                        ));
+                    }
+                    let values = values
+                        .into_iter()
+                        .map(|value| {
+                            value.parse::<$scalar>().map_err(|e| {
+                                format!("invalid coordinate '{}' in '{}': {}", value, s, e)
+                            })
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Ok(<$t>::from_slice(&values))
+                }
+
+                fn components(self) -> impl Iterator<Item = Self::Scalar> {
+                    self.to_array().into_iter()
+                }
+
+                fn manhattan(self, other: Self) -> u64 {
+                    self.manhattan_distance(other).into()
+                }
+
+                fn chebyshev(self, other: Self) -> u64 {
+                    self.chebyshev_distance(other).into()
+                }
+
+                fn euclidean_squared(self, other: Self) -> u128 {
+                    self.components()
+                        .zip(other.components())
+                        .map(|(a, b)| u128::from(a.abs_diff(b)).pow(2))
+                        .sum()
+                }
+
+                fn component_min(self, other: Self) -> Self {
+                    self.min(other)
+                }
+
+                fn component_max(self, other: Self) -> Self {
+                    self.max(other)
+                }
+            }
+        )*
+    };
+}
+
+impl_vector!(IVec2: i32, 2; IVec3: i32, 3; I64Vec2: i64, 2; I64Vec3: i64, 3);
+
+// the smallest and largest of each component, None if there are no points
+pub fn bounding_box<V: Vector>(points: impl IntoIterator<Item = V>) -> Option<(V, V)> {
+    points.into_iter().fold(None, |bounds, point| match bounds {
+        None => Some((point, point)),
+        Some((min, max)) => Some((min.component_min(point), max.component_max(point))),
+    })
+}
+
+// displays a vector the way puzzle inputs write them, e.g. "7,1"
+pub struct Coords<V>(pub V);
+
+impl<V: Vector> Display for Coords<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, component) in self.0.components().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", component)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(I64Vec2::parse("7,-1"), Ok(I64Vec2::new(7, -1)));
+        assert_eq!(IVec3::parse("162,817,812"), Ok(IVec3::new(162, 817, 812)));
+        assert_eq!(
+            IVec3::parse("1,2"),
+            Err("expected 3 comma separated coordinates but found 2 in '1,2'".to_string())
+        );
+        assert_eq!(
+            IVec2::parse("1, 2"),
+            Err("invalid coordinate ' 2' in '1, 2': invalid digit found in string".to_string())
+        );
+    }
+
+    #[test]
+    fn test_distances() {
+        let (a, b) = (IVec3::new(1, -2, 3), IVec3::new(-3, 5, 3));
+        assert_eq!(a.manhattan(b), 11);
+        assert_eq!(a.chebyshev(b), 7);
+        assert_eq!(a.euclidean_squared(b), 65);
+        let (min, max) = (IVec3::splat(i32::MIN), IVec3::splat(i32::MAX));
+        assert_eq!(min.euclidean_squared(max), 3 * u128::from(u32::MAX).pow(2));
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let points = [IVec2::new(7, 1), IVec2::new(11, 7), IVec2::new(2, 5)];
+        assert_eq!(
+            bounding_box(points),
+            Some((IVec2::new(2, 1), IVec2::new(11, 7)))
+        );
+        assert_eq!(bounding_box(Vec::<IVec2>::new()), None);
+    }
+
+    #[test]
+    fn test_coords() {
+        assert_eq!(Coords(I64Vec3::new(1, -2, 3)).to_string(), "1,-2,3");
+    }
+}